@@ -0,0 +1,53 @@
+use raytracing::bench::{self, Benchmark, Measurement};
+use std::time::{Duration, Instant};
+
+const WARM_UP: Duration = Duration::from_millis(300);
+const SAMPLES: usize = 20;
+const SAMPLE_TIME: Duration = Duration::from_millis(50);
+
+/// Times `routine` the way criterion does: a warm-up that also estimates the cost of one iteration, then
+/// samples of as many iterations as fit in the sample time.
+fn measure(routine: &mut dyn FnMut()) -> Measurement {
+    let start = Instant::now();
+    let mut warm_up_iterations = 0u64;
+    while start.elapsed() < WARM_UP {
+        routine();
+        warm_up_iterations += 1;
+    }
+    let per_iteration = start.elapsed().as_secs_f64() / warm_up_iterations as f64;
+    let iterations = ((SAMPLE_TIME.as_secs_f64() / per_iteration) as u64).max(1);
+
+    let samples = (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                routine();
+            }
+            start.elapsed().as_secs_f64() * 1e9 / iterations as f64
+        })
+        .collect();
+    Measurement { samples }
+}
+
+fn report(name: &str, per_iteration: u32, measurement: &Measurement) {
+    let scale = 1.0 / per_iteration as f64;
+    println!(
+        "{:<24} {:>10.2} ns/op (± {:.2})",
+        name,
+        measurement.mean() * scale,
+        measurement.std_dev() * scale
+    );
+}
+
+/// Runs every benchmark, or those whose name contains the first argument, printing the time per operation.
+fn main() {
+    // `cargo bench` passes `--bench` along with any filter.
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with("--"));
+
+    println!("Float4 backend: {}", bench::backend());
+    for Benchmark { name, operations, mut routine } in bench::benchmarks() {
+        if filter.as_ref().is_none_or(|filter| name.contains(filter.as_str())) {
+            report(name, operations, &measure(&mut routine));
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::ray::Ray;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Aabb {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Aabb {
+    pub fn new(a: Point3, b: Point3) -> Self {
+        Aabb {
+            min: Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            max: Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+        }
+    }
+
+    pub fn surrounding(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Point3::new(self.min.x().min(other.min.x()), self.min.y().min(other.min.y()), self.min.z().min(other.min.z())),
+            max: Point3::new(self.max.x().max(other.max.x()), self.max.y().max(other.max.y()), self.max.z().max(other.max.z())),
+        }
+    }
+
+    pub fn overlap(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Point3::new(self.min.x().max(other.min.x()), self.min.y().max(other.min.y()), self.min.z().max(other.min.z())),
+            max: Point3::new(self.max.x().min(other.max.x()), self.max.y().min(other.max.y()), self.max.z().min(other.max.z())),
+        }
+    }
+
+    pub fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> bool {
+        self.clip(ray, t_min, t_max).is_some()
+    }
+
+    /// The part of `t_min..t_max` where the ray is inside the box.
+    pub fn clip(&self, ray: &Ray, mut t_min: Float, mut t_max: Float) -> Option<(Float, Float)> {
+        for axis in 0..3 {
+            let inv_d = 1.0 / ray.direction[axis];
+            let mut t0 = (self.min[axis] - ray.origin[axis]) * inv_d;
+            let mut t1 = (self.max[axis] - ray.origin[axis]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn slab_test() {
+        let bbox = Aabb::new(Point3::new(1.0, 1.0, 1.0), Point3::new(-1.0, -1.0, -1.0));
+        assert!(bbox.hit(&Ray::new(Point3::new(-5.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0)), 0.0, Float::INFINITY));
+        assert!(!bbox.hit(&Ray::new(Point3::new(-5.0, 0.5, 0.5), Vec3::new(-1.0, 0.0, 0.0)), 0.0, Float::INFINITY));
+        assert!(!bbox.hit(&Ray::new(Point3::new(-5.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0)), 0.0, Float::INFINITY));
+        assert!(!bbox.hit(&Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0)), 0.0, 3.0));
+    }
+}
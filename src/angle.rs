@@ -1,11 +1,12 @@
-use std::f32::consts::PI;
+use crate::float::Float;
+use crate::float::consts::PI;
 use std::ops::{Add, Sub, Mul, Div, AddAssign, SubAssign, MulAssign, DivAssign};
 
 #[derive(Copy, Clone, Default, Debug)]
-pub(crate) struct Degrees(pub f32);
+pub(crate) struct Degrees(pub Float);
 
 #[derive(Copy, Clone, Default, Debug)]
-pub(crate) struct Radians(pub f32);
+pub(crate) struct Radians(pub Float);
 
 impl Add for Degrees {
     type Output = Degrees;
@@ -131,25 +132,25 @@ impl From<Radians> for Degrees {
     }
 }
 
-impl From<f32> for Radians {
-    fn from(f: f32) -> Self {
+impl From<Float> for Radians {
+    fn from(f: Float) -> Self {
         Radians(f)
     }
 }
 
-impl From<f32> for Degrees {
-    fn from(f: f32) -> Self {
+impl From<Float> for Degrees {
+    fn from(f: Float) -> Self {
         Degrees(f)
     }
 }
 
-impl From<Degrees> for f32 {
+impl From<Degrees> for Float {
     fn from(d: Degrees) -> Self {
         d.0
     }
 }
 
-impl From<Radians> for f32 {
+impl From<Radians> for Float {
     fn from(r: Radians) -> Self {
         r.0
     }
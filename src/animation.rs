@@ -0,0 +1,266 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::vec3::Vec3;
+use crate::angle::Degrees;
+use crate::float::consts::PI;
+use std::fs;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Keyframe {
+    pub time: Float,
+    pub origin: Point3,
+    pub destination: Point3,
+    pub fov: Degrees,
+    pub focus_dist: Float,
+}
+
+impl Keyframe {
+    fn blend(keys: [&Keyframe; 4], weights: [Float; 4]) -> Keyframe {
+        let mut origin = Vec3::default();
+        let mut destination = Vec3::default();
+        let mut time = 0.0;
+        let mut fov = 0.0;
+        let mut focus_dist = 0.0;
+
+        for (key, weight) in keys.iter().zip(&weights) {
+            time += weight * key.time;
+            origin += *weight * Vec3::from(key.origin);
+            destination += *weight * Vec3::from(key.destination);
+            fov += weight * key.fov.0;
+            focus_dist += weight * key.focus_dist;
+        }
+
+        Keyframe {
+            time,
+            origin: origin.into(),
+            destination: destination.into(),
+            fov: Degrees(fov),
+            focus_dist,
+        }
+    }
+}
+
+impl FromStr for Keyframe {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split_whitespace()
+            .map(|v| v.parse::<Float>().map_err(|_| format!("invalid number `{}`", v)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match values.as_slice() {
+            [time, ox, oy, oz, dx, dy, dz, fov, focus_dist] => Ok(Keyframe {
+                time: *time,
+                origin: Point3::new(*ox, *oy, *oz),
+                destination: Point3::new(*dx, *dy, *dz),
+                fov: Degrees(*fov),
+                focus_dist: *focus_dist,
+            }),
+            _ => Err(format!(
+                "expected `time ox oy oz dx dy dz fov focus_dist`, got {} values",
+                values.len(),
+            )),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum Interpolation {
+    Linear,
+    #[default]
+    Spline,
+}
+
+impl FromStr for Interpolation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Interpolation::Linear),
+            "spline" => Ok(Interpolation::Spline),
+            _ => Err(format!("unknown interpolation `{}`, expected one of: linear, spline", s)),
+        }
+    }
+}
+
+pub(crate) struct CameraPath {
+    keyframes: Vec<Keyframe>,
+    interpolation: Interpolation,
+    // A closed path ends where it starts, so it loops without rendering that pose twice.
+    closed: bool,
+}
+
+impl CameraPath {
+    pub fn new(mut keyframes: Vec<Keyframe>, interpolation: Interpolation) -> Result<Self, String> {
+        if keyframes.is_empty() {
+            return Err("camera path needs at least one keyframe".to_string());
+        }
+        keyframes.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(CameraPath { keyframes, interpolation, closed: false })
+    }
+
+    pub fn from_file(path: &str, interpolation: Interpolation) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("can't read `{}`: {}", path, e))?;
+        let keyframes = contents
+            .lines()
+            .enumerate()
+            .map(|(n, line)| (n, line.split('#').next().unwrap_or("").trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(n, line)| line.parse().map_err(|e| format!("{}:{}: {}", path, n + 1, e)))
+            .collect::<Result<Vec<Keyframe>, _>>()?;
+
+        CameraPath::new(keyframes, interpolation)
+    }
+
+    pub fn turntable(start: &Keyframe, keys: u32, interpolation: Interpolation) -> Self {
+        let offset = Vec3::from(start.origin) - Vec3::from(start.destination);
+        let keyframes = (0..=keys)
+            .map(|i| {
+                let angle = 2.0 * PI * i as Float / keys as Float;
+                let rotated = Vec3::new(
+                    offset.x() * angle.cos() + offset.z() * angle.sin(),
+                    offset.y(),
+                    -offset.x() * angle.sin() + offset.z() * angle.cos(),
+                );
+                Keyframe {
+                    time: i as Float / keys as Float,
+                    origin: start.destination + rotated,
+                    ..*start
+                }
+            })
+            .collect();
+
+        CameraPath { keyframes, interpolation, closed: true }
+    }
+
+    pub fn at(&self, time: Float) -> Keyframe {
+        let last = self.keyframes.len() - 1;
+        let next = self.keyframes
+            .iter()
+            .position(|key| key.time > time)
+            .unwrap_or(last + 1);
+
+        if next == 0 {
+            return self.keyframes[0];
+        }
+        if next > last {
+            return self.keyframes[last];
+        }
+
+        let (i1, i2) = (next - 1, next);
+        let (k1, k2) = (&self.keyframes[i1], &self.keyframes[i2]);
+        let (k0, k3) = if self.closed && last > 1 {
+            let before = if i1 == 0 { last - 1 } else { i1 - 1 };
+            let after = if i2 == last { 1 } else { i2 + 1 };
+            (&self.keyframes[before], &self.keyframes[after])
+        } else {
+            (&self.keyframes[i1.saturating_sub(1)], &self.keyframes[usize::min(i2 + 1, last)])
+        };
+        // Neighbours wrapped around a closed path lie a whole period before or after the segment.
+        let period = self.keyframes[last].time - self.keyframes[0].time;
+        let t0 = if k0.time > k1.time { k0.time - period } else { k0.time };
+        let t3 = if k3.time < k2.time { k3.time + period } else { k3.time };
+
+        let h = k2.time - k1.time;
+        let s = (time - k1.time) / h;
+
+        let weights = match self.interpolation {
+            Interpolation::Linear => [0.0, 1.0 - s, s, 0.0],
+            Interpolation::Spline => {
+                // Catmull-Rom parameterized by time: the tangent at each key is the slope between its neighbours,
+                // so unevenly spaced keys keep a steady speed through them.
+                let (h00, h10) = (2.0 * s * s * s - 3.0 * s * s + 1.0, s * s * s - 2.0 * s * s + s);
+                let (h01, h11) = (-2.0 * s * s * s + 3.0 * s * s, s * s * s - s * s);
+                let (m1, m2) = (h / (k2.time - t0), h / (t3 - k1.time));
+                [-h10 * m1, h00 - h11 * m2, h01 + h10 * m1, h11 * m2]
+            }
+        };
+
+        Keyframe { time, ..Keyframe::blend([k0, k1, k2, k3], weights) }
+    }
+
+    pub fn frames(&self, count: u32) -> impl Iterator<Item = Keyframe> + '_ {
+        let start = self.keyframes[0].time;
+        let duration = self.keyframes[self.keyframes.len() - 1].time - start;
+
+        let steps = if self.closed { count } else { count.saturating_sub(1) };
+
+        (0..count).map(move |i| {
+            let progress = if steps > 0 { i as Float / steps as Float } else { 0.0 };
+            self.at(start + progress * duration)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(time: Float, x: Float) -> Keyframe {
+        Keyframe {
+            time,
+            origin: Point3::new(x, 0.0, 0.0),
+            destination: Point3::new(0.0, 0.0, 0.0),
+            fov: Degrees(20.0 + x),
+            focus_dist: 10.0,
+        }
+    }
+
+    #[test]
+    fn interpolation_passes_through_keyframes() {
+        for interpolation in &[Interpolation::Linear, Interpolation::Spline] {
+            let path = CameraPath::new(vec![key(0.0, 0.0), key(1.0, 4.0), key(3.0, 2.0)], *interpolation).unwrap();
+            for (time, x) in &[(0.0, 0.0), (1.0, 4.0), (3.0, 2.0), (5.0, 2.0)] {
+                let frame = path.at(*time);
+                assert!((frame.origin.x() - x).abs() < 1e-5);
+                assert!((frame.fov.0 - 20.0 - x).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn linear_interpolation_is_proportional() {
+        let path = CameraPath::new(vec![key(0.0, 0.0), key(2.0, 4.0)], Interpolation::Linear).unwrap();
+        assert!((path.at(0.5).origin.x() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn spline_follows_keyframe_times() {
+        // Evenly moving keys at uneven times: a spline that ignored the times would speed up and slow down.
+        let keys = vec![key(0.0, 0.0), key(1.0, 1.0), key(3.0, 3.0), key(4.0, 4.0)];
+        let path = CameraPath::new(keys, Interpolation::Spline).unwrap();
+        for time in &[1.25, 1.5, 2.0, 2.5, 2.75] {
+            assert!((path.at(*time).origin.x() - time).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn frames_span_the_whole_path() {
+        let path = CameraPath::new(vec![key(1.0, 0.0), key(2.0, 4.0)], Interpolation::Linear).unwrap();
+        let frames: Vec<_> = path.frames(5).collect();
+        assert_eq!(5, frames.len());
+        assert_eq!(1.0, frames[0].time);
+        assert_eq!(2.0, frames[4].time);
+    }
+
+    #[test]
+    fn turntable_does_not_repeat_the_first_frame() {
+        let path = CameraPath::turntable(&key(0.0, 4.0), 8, Interpolation::Spline);
+        let frames: Vec<_> = path.frames(8).collect();
+        let (first, last) = (frames[0].origin, frames[7].origin);
+        assert!((first.x() - 4.0).abs() < 1e-5);
+        assert!((Vec3::from(last) - Vec3::from(first)).length() > 1.0);
+        assert!((Vec3::from(last).length() - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn parse_keyframe() {
+        let key: Keyframe = "0.5 13 2 3 0 0 0 20 10".parse().unwrap();
+        assert_eq!(0.5, key.time);
+        assert_eq!(13.0, key.origin.x());
+        assert_eq!(10.0, key.focus_dist);
+        assert!("1 2 3".parse::<Keyframe>().is_err());
+    }
+}
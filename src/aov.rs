@@ -0,0 +1,307 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::vec3::Vec3;
+use crate::film::Film;
+use crate::filter::Filter;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Aov {
+    Depth,
+    Normal,
+    Albedo,
+    ObjectId,
+    MaterialId,
+    Direct,
+    Indirect,
+}
+
+const ALL_AOVS: [Aov; 7] = [
+    Aov::Depth,
+    Aov::Normal,
+    Aov::Albedo,
+    Aov::ObjectId,
+    Aov::MaterialId,
+    Aov::Direct,
+    Aov::Indirect,
+];
+
+impl FromStr for Aov {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ALL_AOVS
+            .iter()
+            .find(|aov| aov.name() == s)
+            .copied()
+            .ok_or_else(|| format!(
+                "unknown AOV `{}`, expected one of: all, {}",
+                s,
+                ALL_AOVS.iter().map(|aov| aov.name()).collect::<Vec<_>>().join(", "),
+            ))
+    }
+}
+
+impl Aov {
+    pub fn parse_list(s: &str) -> Result<Vec<Aov>, String> {
+        if s == "all" {
+            return Ok(ALL_AOVS.to_vec());
+        }
+        let mut aovs = Vec::new();
+        for aov in s.split(',').map(|a| a.trim().parse()) {
+            let aov = aov?;
+            if !aovs.contains(&aov) {
+                aovs.push(aov);
+            }
+        }
+        Ok(aovs)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::ObjectId => "object-id",
+            Aov::MaterialId => "material-id",
+            Aov::Direct => "direct",
+            Aov::Indirect => "indirect",
+        }
+    }
+
+    pub fn channels(self) -> &'static [&'static str] {
+        match self {
+            Aov::Depth => &["Z"],
+            Aov::Normal => &["normal.X", "normal.Y", "normal.Z"],
+            Aov::Albedo => &["albedo.R", "albedo.G", "albedo.B"],
+            Aov::ObjectId => &["objectId"],
+            Aov::MaterialId => &["materialId"],
+            Aov::Direct => &["direct.R", "direct.G", "direct.B"],
+            Aov::Indirect => &["indirect.R", "indirect.G", "indirect.B"],
+        }
+    }
+
+    pub fn is_radiance(self) -> bool {
+        matches!(self, Aov::Albedo | Aov::Direct | Aov::Indirect)
+    }
+}
+
+pub(crate) struct FirstHit {
+    pub depth: Float,
+    pub normal: Vec3,
+    pub albedo: Color,
+    pub object_id: u32,
+    pub material_id: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct PathSample {
+    pub direct: Vec3,
+    pub indirect: Vec3,
+    pub first_hit: Option<FirstHit>,
+}
+
+impl PathSample {
+    pub fn color(&self) -> Color {
+        (self.direct + self.indirect).into()
+    }
+}
+
+pub(crate) struct AovImage {
+    pub aov: Aov,
+    pub pixels: Vec<Color>,
+}
+
+impl AovImage {
+    pub fn display(&self) -> Vec<Color> {
+        match self.aov {
+            Aov::Depth => {
+                let near = self.pixels
+                    .iter()
+                    .map(|p| p.r())
+                    .fold(Float::INFINITY, Float::min);
+                self.pixels
+                    .iter()
+                    .map(|p| {
+                        let d = if p.r().is_finite() { near / p.r() } else { 0.0 };
+                        Color::new(d, d, d)
+                    })
+                    .collect()
+            }
+            Aov::Normal => self.pixels
+                .iter()
+                .map(|p| (0.5 * (p.0 + Vec3::new(1.0, 1.0, 1.0))).into())
+                .collect(),
+            Aov::ObjectId | Aov::MaterialId => self.pixels.iter().map(|p| id_color(p.r())).collect(),
+            Aov::Albedo | Aov::Direct | Aov::Indirect => self.pixels.clone(),
+        }
+    }
+}
+
+pub(crate) struct AovFilm {
+    aovs: Vec<Aov>,
+    width: u32,
+    direct: Option<Film>,
+    indirect: Option<Film>,
+    depth: Vec<(Float, u32)>,
+    normal: Vec<Vec3>,
+    albedo: Vec<(Vec3, u32)>,
+    object_ids: Vec<Float>,
+    material_ids: Vec<Float>,
+    material_indices: HashMap<usize, u32>,
+}
+
+impl AovFilm {
+    pub fn new(aovs: &[Aov], width: u32, height: u32, filter: impl Fn() -> Box<dyn Filter>) -> Self {
+        let size = (width * height) as usize;
+        let film = |aov| if aovs.contains(&aov) { Some(Film::new(width, height, filter())) } else { None };
+        AovFilm {
+            aovs: aovs.to_vec(),
+            width,
+            direct: film(Aov::Direct),
+            indirect: film(Aov::Indirect),
+            depth: vec![(0.0, 0); size],
+            normal: vec![Vec3::default(); size],
+            albedo: vec![(Vec3::default(), 0); size],
+            object_ids: vec![0.0; size],
+            material_ids: vec![0.0; size],
+            material_indices: HashMap::new(),
+        }
+    }
+
+    pub fn add_sample(&mut self, pixel: (u32, u32), (x, y): (Float, Float), sample: &PathSample) {
+        if let Some(film) = &mut self.direct {
+            film.add_sample(x, y, sample.direct.into());
+        }
+        if let Some(film) = &mut self.indirect {
+            film.add_sample(x, y, sample.indirect.into());
+        }
+
+        let idx = (pixel.1 * self.width + pixel.0) as usize;
+        let (albedo, count) = &mut self.albedo[idx];
+        *count += 1;
+
+        if let Some(hit) = &sample.first_hit {
+            *albedo += Vec3::from(hit.albedo);
+            self.depth[idx].0 += hit.depth;
+            self.depth[idx].1 += 1;
+            self.normal[idx] += hit.normal;
+
+            // IDs aren't averaged, so keep those of the first sample that hits anything.
+            if self.depth[idx].1 == 1 {
+                let next_index = self.material_indices.len() as u32 + 1;
+                let material_index = *self.material_indices.entry(hit.material_id).or_insert(next_index);
+                self.object_ids[idx] = (hit.object_id + 1) as Float;
+                self.material_ids[idx] = material_index as Float;
+            }
+        }
+    }
+
+    pub fn resolve(&self) -> Vec<AovImage> {
+        let height = self.depth.len() as u32 / self.width;
+        let rows: Vec<usize> = (0..height)
+            .rev()
+            .flat_map(|y| (0..self.width).map(move |x| (y * self.width + x) as usize))
+            .collect();
+        let scalar = |values: &[Float]| rows.iter().map(|i| Color::new(values[*i], values[*i], values[*i])).collect();
+
+        self.aovs
+            .iter()
+            .map(|aov| {
+                let pixels = match aov {
+                    Aov::Depth => rows
+                        .iter()
+                        .map(|i| match self.depth[*i] {
+                            (_, 0) => Float::INFINITY,
+                            (sum, n) => sum / n as Float,
+                        })
+                        .map(|d| Color::new(d, d, d))
+                        .collect(),
+                    Aov::Normal => rows
+                        .iter()
+                        .map(|i| {
+                            let n = self.normal[*i];
+                            if n.length_squared() > 0.0 { n.unit_vec().into() } else { Color::default() }
+                        })
+                        .collect(),
+                    Aov::Albedo => rows
+                        .iter()
+                        .map(|i| match self.albedo[*i] {
+                            (_, 0) => Color::default(),
+                            (sum, n) => (sum / n as Float).into(),
+                        })
+                        .collect(),
+                    Aov::ObjectId => scalar(&self.object_ids),
+                    Aov::MaterialId => scalar(&self.material_ids),
+                    Aov::Direct => self.direct.as_ref().map(Film::resolve).unwrap_or_default(),
+                    Aov::Indirect => self.indirect.as_ref().map(Film::resolve).unwrap_or_default(),
+                };
+                AovImage { aov: *aov, pixels }
+            })
+            .collect()
+    }
+}
+
+fn id_color(id: Float) -> Color {
+    if id == 0.0 {
+        return Color::default();
+    }
+    let hash = (id as u32).wrapping_mul(0x9e37_79b9);
+    Color::new(
+        (hash >> 24) as Float / 255.0,
+        ((hash >> 16) & 0xff) as Float / 255.0,
+        ((hash >> 8) & 0xff) as Float / 255.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::FilterKind;
+
+    fn hit(depth: Float, material_id: usize) -> PathSample {
+        PathSample {
+            direct: Vec3::new(0.25, 0.25, 0.25),
+            indirect: Vec3::new(0.5, 0.5, 0.5),
+            first_hit: Some(FirstHit {
+                depth,
+                normal: Vec3::new(0.0, 2.0, 0.0),
+                albedo: Color::new(0.5, 0.5, 0.5),
+                object_id: 3,
+                material_id,
+            }),
+        }
+    }
+
+    #[test]
+    fn parse_aov_list() {
+        assert_eq!(vec![Aov::Depth, Aov::Normal], Aov::parse_list("depth, normal,depth").unwrap());
+        assert_eq!(7, Aov::parse_list("all").unwrap().len());
+        assert!(Aov::parse_list("depth,beauty").is_err());
+    }
+
+    #[test]
+    fn aovs_are_averaged_per_pixel() {
+        let mut film = AovFilm::new(&ALL_AOVS, 2, 1, || FilterKind::Box.build(0.5));
+        film.add_sample((0, 0), (0.5, 0.5), &hit(2.0, 0x10));
+        film.add_sample((0, 0), (0.5, 0.5), &hit(4.0, 0x20));
+        film.add_sample((1, 0), (1.5, 0.5), &PathSample::default());
+        film.add_sample((1, 0), (1.5, 0.5), &hit(1.0, 0x20));
+
+        let images = film.resolve();
+        let image = |aov| &images.iter().find(|i| i.aov == aov).unwrap().pixels;
+
+        assert_eq!(3.0, image(Aov::Depth)[0].r());
+        assert_eq!(1.0, image(Aov::Depth)[1].r());
+        assert_eq!(1.0, image(Aov::Normal)[0].g());
+        assert_eq!(0.5, image(Aov::Albedo)[0].r());
+        assert_eq!(0.25, image(Aov::Albedo)[1].r());
+        assert_eq!(4.0, image(Aov::ObjectId)[0].r());
+        assert_eq!(4.0, image(Aov::ObjectId)[1].r());
+        assert_eq!(1.0, image(Aov::MaterialId)[0].r());
+        assert_eq!(2.0, image(Aov::MaterialId)[1].r());
+        assert_eq!(0.25, image(Aov::Direct)[0].r());
+        assert_eq!(0.5, image(Aov::Indirect)[0].r());
+    }
+}
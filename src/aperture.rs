@@ -0,0 +1,148 @@
+use crate::float::Float;
+use crate::vec3::Vec3;
+use crate::pgm;
+use crate::float::consts::PI;
+
+pub(crate) enum Aperture {
+    Circle,
+    Polygon(u32),
+    Mask(ApertureMask),
+}
+
+impl Aperture {
+    pub fn sample(&self, sample: (Float, Float)) -> Vec3 {
+        match self {
+            Aperture::Circle => Vec3::sample_in_unit_disk(sample),
+            Aperture::Polygon(blades) => Aperture::sample_polygon(*blades, sample),
+            Aperture::Mask(mask) => mask.sample(sample),
+        }
+    }
+
+    fn sample_polygon(blades: u32, (u1, u2): (Float, Float)) -> Vec3 {
+        let scaled = u1 * blades as Float;
+        let blade = u32::min(scaled as u32, blades - 1);
+        let a0 = 2.0 * PI * blade as Float / blades as Float;
+        let a1 = 2.0 * PI * (blade + 1) as Float / blades as Float;
+
+        let root = (scaled - blade as Float).sqrt();
+        let (r1, r2) = (root * (1.0 - u2), root * u2);
+
+        r1 * Vec3::new(a0.cos(), a0.sin(), 0.0) + r2 * Vec3::new(a1.cos(), a1.sin(), 0.0)
+    }
+}
+
+/// A grayscale aperture, sampled in proportion to its weights by inverting a CDF over its rows and then over
+/// the cells of the chosen row.
+pub(crate) struct ApertureMask {
+    width: usize,
+    height: usize,
+    row_cdf: Vec<Float>,
+    cell_cdfs: Vec<Float>,
+}
+
+impl ApertureMask {
+    pub fn new(width: usize, height: usize, weights: Vec<Float>) -> Result<Self, String> {
+        if width == 0 || height == 0 || weights.len() != width * height {
+            return Err(format!("aperture mask must have {}x{} weights", width, height));
+        }
+        if weights.iter().all(|w| *w <= 0.0) {
+            return Err("aperture mask is completely opaque".to_string());
+        }
+
+        let mut row_sums = Vec::with_capacity(height);
+        let mut cell_cdfs = Vec::with_capacity(height * (width + 1));
+        for row in weights.chunks(width) {
+            let cdf = cumulative(row.iter().map(|w| w.max(0.0)));
+            row_sums.push(cdf[width]);
+            cell_cdfs.extend(normalized(cdf));
+        }
+        let row_cdf = normalized(cumulative(row_sums.into_iter()));
+        Ok(ApertureMask { width, height, row_cdf, cell_cdfs })
+    }
+
+    pub fn from_pgm(path: &str) -> Result<Self, String> {
+        let image = pgm::read(path)?;
+        ApertureMask::new(image.width, image.height, image.values)
+    }
+
+    fn sample(&self, (u1, u2): (Float, Float)) -> Vec3 {
+        let (row, fy) = invert(&self.row_cdf, u2);
+        let (col, fx) = invert(&self.cell_cdfs[row * (self.width + 1)..(row + 1) * (self.width + 1)], u1);
+        let x = 2.0 * (col as Float + fx) / self.width as Float - 1.0;
+        let y = 1.0 - 2.0 * (row as Float + fy) / self.height as Float;
+        Vec3::new(x, y, 0.0)
+    }
+}
+
+fn cumulative(weights: impl Iterator<Item = Float>) -> Vec<Float> {
+    let mut cdf = vec![0.0];
+    for w in weights {
+        cdf.push(cdf[cdf.len() - 1] + w);
+    }
+    cdf
+}
+
+/// Rows that are entirely opaque are never picked, so they can keep an all-zero CDF.
+fn normalized(mut cdf: Vec<Float>) -> Vec<Float> {
+    let total = cdf[cdf.len() - 1];
+    if total > 0.0 {
+        cdf.iter_mut().for_each(|c| *c /= total);
+    }
+    cdf
+}
+
+/// The cell whose CDF interval holds `u`, and where in that interval it falls. Empty cells have empty intervals,
+/// so they're skipped.
+fn invert(cdf: &[Float], u: Float) -> (usize, Float) {
+    let cell = (cdf.partition_point(|c| *c <= u) - 1).min(cdf.len() - 2);
+    let width = cdf[cell + 1] - cdf[cell];
+    let offset = if width > 0.0 { (u - cdf[cell]) / width } else { 0.5 };
+    (cell, offset.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random;
+    use rand::Rng;
+
+    #[test]
+    fn polygon_samples_lie_in_unit_disk() {
+        let aperture = Aperture::Polygon(6);
+        let mut rng = random::rng();
+        for _ in 0..1000 {
+            let p = aperture.sample((rng.gen(), rng.gen()));
+            assert!(p.length_squared() <= 1.0 + 1e-6);
+            assert_eq!(0.0, p.z());
+        }
+    }
+
+    fn grid(n: u32) -> impl Iterator<Item = (Float, Float)> {
+        (0..n * n).map(move |i| (((i % n) as Float + 0.5) / n as Float, ((i / n) as Float + 0.5) / n as Float))
+    }
+
+    #[test]
+    fn mask_samples_only_transparent_cells() {
+        let mask = ApertureMask::new(2, 2, vec![0.0, 1.0, 0.0, 0.0]).unwrap();
+        let aperture = Aperture::Mask(mask);
+        for sample in grid(32) {
+            let p = aperture.sample(sample);
+            assert!((0.0..=1.0).contains(&p.x()) && (0.0..=1.0).contains(&p.y()), "{:?} -> {:?}", sample, p);
+        }
+    }
+
+    #[test]
+    fn mask_samples_follow_weights() {
+        let mask = ApertureMask::new(2, 1, vec![1.0, 3.0]).unwrap();
+        let left = grid(32).filter(|s| mask.sample(*s).x() < 0.0).count();
+        assert_eq!(32 * 32 / 4, left);
+
+        let p = mask.sample((0.125, 0.5));
+        assert!((p.x() + 0.5).abs() < 1e-6 && p.y().abs() < 1e-6);
+    }
+
+    #[test]
+    fn opaque_mask_is_rejected() {
+        assert!(ApertureMask::new(1, 1, vec![0.0]).is_err());
+    }
+}
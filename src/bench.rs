@@ -0,0 +1,231 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hit::{Hit, HitRecord};
+use crate::sphere::Sphere;
+use crate::material::{Dielectric, Lambertian, Material, Metal, Plastic};
+use crate::sampler::SamplerKind;
+use crate::settings::Settings;
+use crate::color::Color;
+use crate::random;
+use crate::scene::{self, SceneKind};
+use crate::simd::Float4;
+use rand::Rng;
+use std::hint::black_box;
+use std::rc::Rc;
+
+/// Every benchmark builds its inputs from this seed, so runs compare the same work.
+const SEED: u64 = 0x5eed;
+
+/// Inputs of the benchmarks run by `cargo bench`, which times them in `benches/benchmarks.rs`.
+pub struct Benchmark {
+    pub name: &'static str,
+    /// Operations done by one call of `routine`, so times can be reported per operation.
+    pub operations: u32,
+    pub routine: Box<dyn FnMut()>,
+}
+
+impl Benchmark {
+    fn new(name: &'static str, operations: u32, routine: impl FnMut() + 'static) -> Self {
+        Benchmark { name, operations, routine: Box::new(routine) }
+    }
+}
+
+pub struct Measurement {
+    /// Nanoseconds per iteration of each sample.
+    pub samples: Vec<f64>,
+}
+
+impl Measurement {
+    pub fn mean(&self) -> f64 {
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        let mean = self.mean();
+        let variance = self.samples.iter().map(|s| (s - mean) * (s - mean)).sum::<f64>() / (self.samples.len() - 1) as f64;
+        variance.sqrt()
+    }
+}
+
+fn random_vectors(count: usize) -> Vec<Vec3> {
+    (0..count).map(|_| Vec3::random_range(-1.0, 1.0)).collect()
+}
+
+/// Rays from random points on a sphere of `radius` around the origin towards random points near it.
+fn random_rays(count: usize, radius: Float, spread: Float) -> Vec<Ray> {
+    (0..count)
+        .map(|_| {
+            let origin = radius * Vec3::random_range(-1.0, 1.0).unit_vec();
+            let target = Vec3::random_range(-spread, spread);
+            Ray::new(Point3::from(origin), target - origin)
+        })
+        .collect()
+}
+
+/// Which implementation the `Vec3` lanes compile to. Comparing a build with `--features simd` to one without
+/// shows what the SIMD lanes gain.
+pub fn backend() -> &'static str {
+    Float4::BACKEND
+}
+
+/// Every benchmark, in the order they're reported.
+pub fn benchmarks() -> Vec<Benchmark> {
+    let mut benchmarks = vec3_ops();
+    benchmarks.extend(intersection());
+    benchmarks.extend(scattering());
+    benchmarks.extend(rendering());
+    benchmarks
+}
+
+const BATCH: usize = 1024;
+
+fn vec3_ops() -> Vec<Benchmark> {
+    random::seed(SEED);
+    let (a, b) = (Rc::new(random_vectors(BATCH)), Rc::new(random_vectors(BATCH)));
+    let (a1, b1, a2, b2) = (a.clone(), b.clone(), a.clone(), b.clone());
+    vec![
+        Benchmark::new("vec3/add_mul", BATCH as u32, move || {
+            for (a, b) in black_box(&a).iter().zip(b.iter()) {
+                black_box(*a * 0.5 + *b * *a - *b);
+            }
+        }),
+        Benchmark::new("vec3/dot", BATCH as u32, move || {
+            for (a, b) in black_box(&a1).iter().zip(b1.iter()) {
+                black_box(a.dot(b));
+            }
+        }),
+        Benchmark::new("vec3/cross_unit", BATCH as u32, move || {
+            for (a, b) in black_box(&a2).iter().zip(b2.iter()) {
+                black_box(a.cross(b).unit_vec());
+            }
+        }),
+    ]
+}
+
+fn intersection() -> Vec<Benchmark> {
+    let mut benchmarks = Vec::new();
+
+    random::seed(SEED);
+    let material: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let sphere = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, material.clone());
+    // Aimed at a box wider than the sphere, so some rays miss.
+    let rays = random_rays(BATCH, 4.0, 1.5);
+    benchmarks.push(Benchmark::new("sphere/hit", BATCH as u32, move || {
+        for ray in black_box(&rays) {
+            black_box(sphere.hit(ray, 0.0, Float::INFINITY));
+        }
+    }));
+
+    random::seed(SEED);
+    let world = scene::build(SceneKind::RandomSpheres, None).expect("built-in scene").world;
+    let rays: Vec<Ray> = (0..BATCH)
+        .map(|_| {
+            let mut rng = random::rng();
+            let target = Point3::new(rng.gen_range(-11.0, 11.0), rng.gen_range(0.0, 1.0), rng.gen_range(-11.0, 11.0));
+            let origin = Point3::new(13.0, 2.0, 3.0);
+            Ray::new(origin, Vec3::from(target) - Vec3::from(origin))
+        })
+        .collect();
+    benchmarks.push(Benchmark::new("hittable_list/random", BATCH as u32, move || {
+        for ray in black_box(&rays) {
+            black_box(world.hit(ray, 0.0, Float::INFINITY));
+        }
+    }));
+
+    random::seed(SEED);
+    let knot = scene::torus_knot(material);
+    let rays = random_rays(BATCH, 4.0, 0.8);
+    benchmarks.push(Benchmark::new("bvh/mesh", BATCH as u32, move || {
+        for ray in black_box(&rays) {
+            black_box(knot.hit(ray, 0.0, Float::INFINITY));
+        }
+    }));
+
+    random::seed(SEED);
+    let world = scene::build(SceneKind::Instances, None).expect("built-in scene").world;
+    let rays: Vec<Ray> = (0..BATCH)
+        .map(|_| {
+            let mut rng = random::rng();
+            let origin = Point3::new(rng.gen_range(-40.0, 40.0), rng.gen_range(1.0, 6.0), rng.gen_range(-40.0, 40.0));
+            Ray::new(origin, Vec3::new(rng.gen_range(-1.0, 1.0), rng.gen_range(-0.5, -0.05), rng.gen_range(-1.0, 1.0)))
+        })
+        .collect();
+    benchmarks.push(Benchmark::new("bvh/instances", BATCH as u32, move || {
+        for ray in black_box(&rays) {
+            black_box(world.hit(ray, 0.0, Float::INFINITY));
+        }
+    }));
+
+    benchmarks
+}
+
+/// Each material scatters the same sphere hits, seen from both sides for the dielectric.
+fn scattering() -> Vec<Benchmark> {
+    random::seed(SEED);
+    let sphere = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, Rc::new(Lambertian::new(Color::default())));
+    let hits: Rc<Vec<(Ray, HitRecord)>> = Rc::new(
+        random_rays(4 * BATCH, 4.0, 1.5)
+            .into_iter()
+            .filter_map(|ray| sphere.hit(&ray, 0.0, Float::INFINITY).map(|rec| (ray, rec)))
+            .take(BATCH)
+            .collect(),
+    );
+    let materials: Vec<(&str, Rc<dyn Material>)> = vec![
+        ("scatter/lambertian", Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))),
+        ("scatter/metal", Rc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.3))),
+        ("scatter/plastic", Rc::new(Plastic::new(Color::new(0.7, 0.1, 0.1), 1.5, 0.1))),
+        ("scatter/dielectric", Rc::new(Dielectric::new(1.5))),
+    ];
+
+    materials
+        .into_iter()
+        .map(|(name, material)| {
+            let hits = hits.clone();
+            // The uniform sampler needs no per-sample setup, which would dominate the timing.
+            let mut sampler = SamplerKind::Uniform.build(1);
+            Benchmark::new(name, hits.len() as u32, move || {
+                for (ray, rec) in black_box(hits.iter()) {
+                    black_box(material.scatter(ray, rec, sampler.as_mut()));
+                }
+            })
+        })
+        .collect()
+}
+
+/// A whole frame of the random spheres scene at a tiny size, timed per path.
+fn rendering() -> Vec<Benchmark> {
+    const WIDTH: u32 = 48;
+    const HEIGHT: u32 = 27;
+    const SAMPLES_PER_PIXEL: u32 = 2;
+
+    random::seed(SEED);
+    let settings = Settings::default();
+    let world = scene::build(SceneKind::RandomSpheres, None).expect("built-in scene").world;
+    let cam = crate::camera(&settings, &world, &crate::default_view(&settings));
+    vec![Benchmark::new("render/random_48x27", WIDTH * HEIGHT * SAMPLES_PER_PIXEL, move || {
+        // Reseeding keeps every iteration tracing the same paths.
+        random::seed(SEED);
+        let mut sampler = settings.sampler.build(SAMPLES_PER_PIXEL);
+        for j in 0..HEIGHT {
+            for i in 0..WIDTH {
+                for s in 0..SAMPLES_PER_PIXEL {
+                    black_box(crate::sample_pixel(&world, cam.as_ref(), sampler.as_mut(), (i, j), s, (WIDTH, HEIGHT)));
+                }
+            }
+        }
+    })]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spread_of_samples() {
+        let measurement = Measurement { samples: vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] };
+        assert_eq!(5.0, measurement.mean());
+        assert!((measurement.std_dev() - (32.0f64 / 7.0).sqrt()).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,266 @@
+use crate::float::Float;
+use crate::aabb::Aabb;
+use crate::point3::Point3;
+use crate::ray::Ray;
+use crate::simd::Float4;
+use crate::stats;
+use crate::vec3::Vec3;
+
+const MAX_LEAF_SIZE: usize = 4;
+
+struct BvhNode {
+    bounds: Aabb,
+    /// Children start here for interior nodes, primitives for leaves.
+    first: usize,
+    count: usize,
+}
+
+#[derive(Copy, Clone)]
+enum Child {
+    Empty,
+    Node(usize),
+    /// Primitives `first..first + count` of the index list.
+    Leaf { first: usize, count: usize },
+}
+
+/// Up to four children with their bounds laid out by axis, one lane per child, so a ray is tested against
+/// all of them at once. Empty lanes hold inverted boxes that no ray enters.
+struct WideNode {
+    min: [Float4; 3],
+    max: [Float4; 3],
+    children: [Child; 4],
+}
+
+/// Bounding volume hierarchy over primitives identified by their index in the slice it was built from.
+/// It is built as a binary tree and then collapsed into four-wide nodes for traversal.
+pub(crate) struct Bvh {
+    nodes: Vec<WideNode>,
+    indices: Vec<usize>,
+    bounds: Option<Aabb>,
+}
+
+impl Bvh {
+    pub fn new(boxes: &[Aabb]) -> Self {
+        let mut indices: Vec<usize> = (0..boxes.len()).collect();
+        let mut binary = Vec::new();
+        if !boxes.is_empty() {
+            binary.push(BvhNode { bounds: boxes[0], first: 0, count: boxes.len() });
+            subdivide(&mut binary, &mut indices, 0, boxes);
+        }
+
+        let mut bvh = Bvh { nodes: Vec::new(), indices, bounds: binary.first().map(|root| root.bounds) };
+        if !binary.is_empty() {
+            bvh.collapse(&binary, 0);
+        }
+        bvh
+    }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.bounds
+    }
+
+    /// Adds a wide node for the binary subtree at `root`, opening its largest interior descendants until
+    /// there are four children, and returns its index.
+    fn collapse(&mut self, binary: &[BvhNode], root: usize) -> usize {
+        let area = |node: usize| {
+            let size = Vec3::from(binary[node].bounds.max) - Vec3::from(binary[node].bounds.min);
+            size.x() * size.y() + size.y() * size.z() + size.z() * size.x()
+        };
+        let mut group = vec![root];
+        while group.len() < 4 {
+            let largest = (0..group.len())
+                .filter(|i| binary[group[*i]].count == 0)
+                .max_by(|a, b| area(group[*a]).partial_cmp(&area(group[*b])).unwrap_or(std::cmp::Ordering::Equal));
+            match largest {
+                Some(i) => {
+                    let first = binary[group.swap_remove(i)].first;
+                    group.extend_from_slice(&[first, first + 1]);
+                },
+                None => break,
+            }
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(WideNode { min: [Float4::splat(0.0); 3], max: [Float4::splat(0.0); 3], children: [Child::Empty; 4] });
+        let (mut min, mut max) = ([[Float::INFINITY; 4]; 3], [[Float::NEG_INFINITY; 4]; 3]);
+        let mut children = [Child::Empty; 4];
+        for (lane, node) in group.into_iter().enumerate() {
+            let BvhNode { bounds, first, count } = binary[node];
+            for axis in 0..3 {
+                min[axis][lane] = bounds.min[axis as u8];
+                max[axis][lane] = bounds.max[axis as u8];
+            }
+            children[lane] = if count == 0 { Child::Node(self.collapse(binary, node)) } else { Child::Leaf { first, count } };
+        }
+        self.nodes[index] = WideNode { min: min.map(Float4::from_array), max: max.map(Float4::from_array), children };
+        index
+    }
+
+    /// Calls `hit` with each primitive whose leaf the ray reaches and the current closest distance,
+    /// returning the closest result. `hit` reports the distance along with its result.
+    pub fn hit<T>(&self, ray: &Ray, t_min: Float, mut t_max: Float, mut hit: impl FnMut(usize, Float) -> Option<(Float, T)>) -> Option<T> {
+        let mut closest = None;
+        let (mut visits, mut tests) = (0, 0);
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push((Child::Node(0), t_min));
+        }
+
+        let inv_d = [0, 1, 2].map(|axis: u8| 1.0 / ray.direction[axis]);
+        let origin = [0, 1, 2].map(|axis: u8| Float4::splat(ray.origin[axis]));
+        while let Some((child, t_enter)) = stack.pop() {
+            if t_enter > t_max {
+                continue;
+            }
+
+            match child {
+                Child::Empty => {},
+                Child::Leaf { first, count } => {
+                    tests += count as u64;
+                    for primitive in &self.indices[first..first + count] {
+                        if let Some((t, result)) = hit(*primitive, t_max) {
+                            t_max = t;
+                            closest = Some(result);
+                        }
+                    }
+                },
+                Child::Node(node) => {
+                    visits += 1;
+                    let node = &self.nodes[node];
+                    // The slab test of `Aabb::clip` on four boxes. Picking the near plane by the sign of the
+                    // direction instead of swapping lets `max` and `min` skip NaN slabs as `clip` does.
+                    let (mut near, mut far) = (Float4::splat(t_min), Float4::splat(t_max));
+                    for axis in 0..3 {
+                        let (entry, exit) = if inv_d[axis] < 0.0 { (node.max[axis], node.min[axis]) } else { (node.min[axis], node.max[axis]) };
+                        let inv_d = Float4::splat(inv_d[axis]);
+                        near = ((entry - origin[axis]) * inv_d).max(near);
+                        far = ((exit - origin[axis]) * inv_d).min(far);
+                    }
+
+                    let (mask, near) = (near.le_mask(far), near.to_array());
+                    let mut entered = [(0.0, Child::Empty); 4];
+                    let mut count = 0;
+                    for lane in (0..4).filter(|lane| mask & 1 << lane != 0) {
+                        entered[count] = (near[lane], node.children[lane]);
+                        count += 1;
+                    }
+                    // Farthest first, so the nearest child is popped next.
+                    entered[..count].sort_unstable_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+                    stack.extend(entered[..count].iter().map(|(t, child)| (*child, *t)));
+                },
+            }
+        }
+
+        stats::count_bvh_node_visits(visits);
+        stats::count_intersection_tests(tests);
+        closest
+    }
+}
+
+fn subdivide(nodes: &mut Vec<BvhNode>, indices: &mut [usize], node: usize, boxes: &[Aabb]) {
+    let (first, count) = (nodes[node].first, nodes[node].count);
+    let primitives = &mut indices[first..first + count];
+
+    let centroid = |i: usize| {
+        let b = boxes[i];
+        Point3::new(0.5 * (b.min.x() + b.max.x()), 0.5 * (b.min.y() + b.max.y()), 0.5 * (b.min.z() + b.max.z()))
+    };
+    let bounds = primitives.iter().fold(boxes[primitives[0]], |acc, i| acc.surrounding(&boxes[*i]));
+    let centroids = primitives.iter().fold(Aabb::new(centroid(primitives[0]), centroid(primitives[0])), |acc, i| {
+        acc.surrounding(&Aabb::new(centroid(*i), centroid(*i)))
+    });
+    nodes[node].bounds = bounds;
+
+    let extent = |axis: u8| centroids.max[axis] - centroids.min[axis];
+    let axis = (0..3).fold(0, |best, axis| if extent(axis) > extent(best) { axis } else { best });
+    if count <= MAX_LEAF_SIZE || extent(axis) <= 0.0 {
+        return;
+    }
+
+    // Median split along the widest centroid axis keeps the tree balanced.
+    let mid = count / 2;
+    primitives.select_nth_unstable_by(mid, |a, b| {
+        centroid(*a)[axis].partial_cmp(&centroid(*b)[axis]).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let left = nodes.len();
+    nodes.push(BvhNode { bounds, first, count: mid });
+    nodes.push(BvhNode { bounds, first: first + mid, count: count - mid });
+    nodes[node] = BvhNode { bounds, first: left, count: 0 };
+    subdivide(nodes, indices, left, boxes);
+    subdivide(nodes, indices, left + 1, boxes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_boxes(count: usize) -> Vec<Aabb> {
+        (0..count)
+            .map(|i| {
+                let x = 2.0 * i as Float;
+                Aabb::new(Point3::new(x, 0.0, 0.0), Point3::new(x + 1.0, 1.0, 1.0))
+            })
+            .collect()
+    }
+
+    fn build(boxes: &[Aabb], ray: &Ray) -> Bvh {
+        let bvh = Bvh::new(boxes);
+        assert!(bvh.bounds().unwrap().hit(ray, 0.0, Float::INFINITY));
+        bvh
+    }
+
+    #[test]
+    fn finds_closest_primitive() {
+        let boxes = unit_boxes(37);
+        let ray = Ray::new(Point3::new(100.0, 0.5, 0.5), Vec3::new(-1.0, 0.0, 0.0));
+        let bvh = build(&boxes, &ray);
+
+        let closest = bvh.hit(&ray, 0.0, Float::INFINITY, |i, t_max| {
+            let t = 100.0 - boxes[i].max.x();
+            if t < t_max { Some((t, i)) } else { None }
+        });
+        assert_eq!(Some(36), closest);
+    }
+
+    #[test]
+    fn skips_subtrees_beyond_closest_hit() {
+        let boxes = unit_boxes(64);
+        let ray = Ray::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        let bvh = build(&boxes, &ray);
+
+        let mut visited = 0;
+        let closest = bvh.hit(&ray, 0.0, Float::INFINITY, |i, t_max| {
+            visited += 1;
+            let t = boxes[i].min.x() + 1.0;
+            if t < t_max { Some((t, i)) } else { None }
+        });
+        assert_eq!(Some(0), closest);
+        assert!(visited < 64);
+    }
+
+    #[test]
+    fn grazing_rays_enter_boxes_like_aabb() {
+        // The ray runs inside the plane of the boxes' bottom faces, where the slab distances are NaN.
+        let boxes = unit_boxes(9);
+        let bvh = Bvh::new(&boxes);
+        let ray = Ray::new(Point3::new(-1.0, 0.0, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        let mut entered = Vec::new();
+        bvh.hit(&ray, 0.0, Float::INFINITY, |i, _| -> Option<(Float, ())> {
+            entered.push(i);
+            None
+        });
+        entered.sort_unstable();
+        let expected: Vec<usize> = (0..9).filter(|i| boxes[*i].hit(&ray, 0.0, Float::INFINITY)).collect();
+        assert_eq!(9, expected.len());
+        assert_eq!(expected, entered);
+    }
+
+    #[test]
+    fn empty_hierarchy() {
+        let bvh = Bvh::new(&[]);
+        assert!(bvh.bounds().is_none());
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(bvh.hit(&ray, 0.0, Float::INFINITY, |_, _| Some((1.0, ()))).is_none());
+    }
+}
@@ -1,9 +1,97 @@
+use crate::float::Float;
 use crate::point3::Point3;
 use crate::vec3::Vec3;
 use crate::ray::Ray;
 use crate::angle::{Degrees, Radians};
+use crate::aperture::Aperture;
+use crate::hit::Hit;
+use crate::fisheye::FisheyeMapping;
+use std::str::FromStr;
 
-pub(crate) struct Camera {
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Lens {
+    pub focal_length: Float,
+    pub f_stop: Float,
+    pub sensor_width: Float,
+    pub sensor_height: Float,
+}
+
+impl Default for Lens {
+    fn default() -> Self {
+        Lens { focal_length: 50.0, f_stop: 2.8, sensor_width: 36.0, sensor_height: 24.0 }
+    }
+}
+
+impl Lens {
+    pub fn vertical_fov(&self, aspect_ratio: Float) -> Degrees {
+        let sensor_height = Float::min(self.sensor_height, self.sensor_width / aspect_ratio);
+        Radians(2.0 * (sensor_height / (2.0 * self.focal_length)).atan()).into()
+    }
+
+    pub fn aperture(&self) -> Float {
+        self.focal_length / self.f_stop / 1000.0
+    }
+}
+
+pub(crate) trait Camera {
+    fn get_ray(&self, u: Float, v: Float, lens_sample: (Float, Float)) -> Option<Ray>;
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum Projection {
+    #[default]
+    Perspective,
+    Orthographic,
+    Fisheye(FisheyeMapping),
+    Equirectangular,
+}
+
+impl FromStr for Projection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "perspective" => Ok(Projection::Perspective),
+            "orthographic" => Ok(Projection::Orthographic),
+            "fisheye" | "fisheye-equidistant" => Ok(Projection::Fisheye(FisheyeMapping::Equidistant)),
+            "fisheye-equisolid" => Ok(Projection::Fisheye(FisheyeMapping::Equisolid)),
+            "equirectangular" => Ok(Projection::Equirectangular),
+            _ => Err(format!(
+                "unknown projection `{}`, expected one of: perspective, orthographic, \
+                 fisheye-equidistant, fisheye-equisolid, equirectangular",
+                s,
+            )),
+        }
+    }
+}
+
+impl Projection {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::Perspective => "perspective",
+            Projection::Orthographic => "orthographic",
+            Projection::Fisheye(FisheyeMapping::Equidistant) => "fisheye-equidistant",
+            Projection::Fisheye(FisheyeMapping::Equisolid) => "fisheye-equisolid",
+            Projection::Equirectangular => "equirectangular",
+        }
+    }
+
+    pub fn default_fov(&self) -> Degrees {
+        match self {
+            Projection::Fisheye(_) => Degrees(180.0),
+            _ => Degrees(20.0),
+        }
+    }
+}
+
+pub(crate) fn orthonormal_basis(origin: Point3, destination: Point3, view_up: Vec3) -> (Vec3, Vec3, Vec3) {
+    let w = (Vec3::from(origin) - Vec3::from(destination)).unit_vec();
+    let u = Vec3::cross(&view_up, &w).unit_vec();
+    let v = Vec3::cross(&w, &u);
+    (u, v, w)
+}
+
+pub(crate) struct PerspectiveCamera {
     origin: Point3,
     lower_left_corner: Point3,
     horizontal: Vec3,
@@ -11,51 +99,117 @@ pub(crate) struct Camera {
     u: Vec3,
     v: Vec3,
     w: Vec3,
-    lens_radius: f32,
+    viewport_width: Float,
+    viewport_height: Float,
+    lens_radius: Float,
+    aperture: Aperture,
 }
 
-impl Camera {
+impl PerspectiveCamera {
     pub fn new(
         origin: Point3,
         destination: Point3,
         view_up: Vec3,
         vertical_fov: Degrees,
-        aspect_ratio: f32,
-        aperture: f32,
-        focus_dist: f32,
-    ) -> Camera {
+        aspect_ratio: Float,
+        aperture: Float,
+        focus_dist: Float,
+    ) -> Self {
         let theta = Radians::from(vertical_fov);
-        let h = (f32::from(theta) / 2.0).tan();
+        let h = (Float::from(theta) / 2.0).tan();
         let viewport_height = 2.0 * h;
         let viewport_width = aspect_ratio * viewport_height;
 
-        let w = (Vec3::from(origin) - Vec3::from(destination)).unit_vec();
-        let u = Vec3::cross(&view_up, &w).unit_vec();
-        let v = Vec3::cross(&w, &u);
-
-        let horizontal = focus_dist * viewport_width * u;
-        let vertical = focus_dist * viewport_height * v;
-        let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0 - focus_dist * w;
+        let (u, v, w) = orthonormal_basis(origin, destination, view_up);
 
-        Camera {
+        let mut camera = PerspectiveCamera {
             origin,
-            lower_left_corner,
-            horizontal,
-            vertical,
+            lower_left_corner: origin,
+            horizontal: Vec3::default(),
+            vertical: Vec3::default(),
             u,
             v,
             w,
+            viewport_width,
+            viewport_height,
             lens_radius: aperture / 2.0,
-        }
+            aperture: Aperture::Circle,
+        };
+        camera.set_focus_dist(focus_dist);
+        camera
+    }
+
+    pub fn with_lens(
+        origin: Point3,
+        destination: Point3,
+        view_up: Vec3,
+        lens: &Lens,
+        aspect_ratio: Float,
+        focus_dist: Float,
+    ) -> Self {
+        PerspectiveCamera::new(
+            origin,
+            destination,
+            view_up,
+            lens.vertical_fov(aspect_ratio),
+            aspect_ratio,
+            lens.aperture(),
+            focus_dist,
+        )
+    }
+
+    pub fn set_aperture(&mut self, aperture: Aperture) {
+        self.aperture = aperture;
+    }
+
+    pub fn set_focus_dist(&mut self, focus_dist: Float) {
+        self.horizontal = focus_dist * self.viewport_width * self.u;
+        self.vertical = focus_dist * self.viewport_height * self.v;
+        self.lower_left_corner = self.origin - self.horizontal / 2.0 - self.vertical / 2.0 - focus_dist * self.w;
     }
 
-    pub fn get_ray(&self, u: f32, v: f32) -> Ray {
-        let rd = self.lens_radius * Vec3::random_in_unit_disk();
+    pub fn autofocus(&mut self, world: &impl Hit, s: Float, t: Float) -> Option<Float> {
+        let ray = Ray::new(
+            self.origin,
+            (self.lower_left_corner + s * self.horizontal + t * self.vertical - Vec3::from(self.origin)).into(),
+        );
+        let hit_rec = world.hit(&ray, 0.0, Float::INFINITY)?;
+        let focus_dist = Vec3::dot(&(Vec3::from(hit_rec.p) - Vec3::from(self.origin)), &-self.w);
+
+        self.set_focus_dist(focus_dist);
+        Some(focus_dist)
+    }
+}
+
+impl Camera for PerspectiveCamera {
+    fn get_ray(&self, u: Float, v: Float, lens_sample: (Float, Float)) -> Option<Ray> {
+        let rd = self.lens_radius * self.aperture.sample(lens_sample);
         let offset = self.u * rd.x() + self.v * rd.y();
 
-        Ray::new(
+        Some(Ray::new(
             self.origin + offset,
             (self.lower_left_corner + u * self.horizontal + v * self.vertical - Vec3::from(self.origin) - offset).into(),
-        )
+        ))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lens_field_of_view() {
+        let lens = Lens::default();
+        let fov = Float::from(lens.vertical_fov(1.5));
+        assert!((fov - 26.9915).abs() < 1e-3);
+
+        let fov_wide_image = Float::from(lens.vertical_fov(3.0));
+        assert!((fov_wide_image - 13.6855).abs() < 1e-3);
+    }
+
+    #[test]
+    fn lens_aperture_diameter() {
+        let lens = Lens { focal_length: 50.0, f_stop: 2.0, ..Lens::default() };
+        assert!((lens.aperture() - 0.025).abs() < 1e-6);
+    }
+}
@@ -1,23 +1,24 @@
+use crate::float::Float;
 use crate::vec3::Vec3;
 use std::fmt::{Display, Formatter};
 use std::fmt;
+use crate::random;
 use rand::Rng;
 
 #[derive(Copy, Clone, Debug, Default)]
 pub(crate) struct Color(pub Vec3);
 
 impl Color {
-    pub fn new(r: f32, g: f32, b: f32) -> Self {
+    pub fn new(r: Float, g: Float, b: Float) -> Self {
         Color(Vec3::new(r, g, b))
     }
 
     pub fn random() -> Self {
-        let mut rng = rand::thread_rng();
-        Color(Vec3 { vec: rng.gen() })
+        Color(Vec3::random())
     }
 
-    pub fn random_range(from: f32, to: f32) -> Color {
-        let mut rng = rand::thread_rng();
+    pub fn random_range(from: Float, to: Float) -> Color {
+        let mut rng = random::rng();
         Color(Vec3::new(
             rng.gen_range(from, to),
             rng.gen_range(from, to),
@@ -25,15 +26,15 @@ impl Color {
         ))
     }
 
-    pub fn r(&self) -> f32 {
+    pub fn r(&self) -> Float {
         self.0.x()
     }
 
-    pub fn g(&self) -> f32 {
+    pub fn g(&self) -> Float {
         self.0.y()
     }
 
-    pub fn b(&self) -> f32 {
+    pub fn b(&self) -> Float {
         self.0.z()
     }
 }
@@ -41,9 +42,9 @@ impl Color {
 impl Display for Color {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{} {} {}",
-               (255.0 * self.r()) as i32,
-               (255.0 * self.g()) as i32,
-               (255.0 * self.b()) as i32,
+               (255.0 * self.r().clamp(0.0, 1.0)) as i32,
+               (255.0 * self.g().clamp(0.0, 1.0)) as i32,
+               (255.0 * self.b().clamp(0.0, 1.0)) as i32,
         )
     }
 }
@@ -53,3 +54,13 @@ impl From<Vec3> for Color {
         Color(v)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_clamps_out_of_range_values() {
+        assert_eq!("255 0 127", Color::new(2.5, -0.3, 0.5).to_string());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::hit::{Hit, HitRecord};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::roots::solve_quadratic;
+use crate::scene_file::{self, SceneWriter};
+use crate::float::consts::PI;
+use std::rc::Rc;
+
+pub(crate) struct Cone {
+    base: Point3,
+    radius: Float,
+    height: Float,
+    capped: bool,
+    material: Rc<dyn Material>,
+}
+
+impl Cone {
+    /// Vertical cone with its base disk centered on `base` and the apex `height` above it.
+    pub fn new(base: Point3, radius: Float, height: Float, capped: bool, material: Rc<dyn Material>) -> Self {
+        Cone { base, radius, height, capped, material }
+    }
+}
+
+impl Hit for Cone {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let apex = Vec3::from(self.base) + Vec3::new(0.0, self.height, 0.0);
+        let o = Vec3::from(ray.origin) - apex;
+        let d = ray.direction;
+        let k2 = (self.radius / self.height).powi(2);
+
+        let mut closest: Option<(Float, Vec3, (Float, Float))> = None;
+        let mut consider = |t: Float, normal: Vec3, uv: (Float, Float)| {
+            if t > t_min && t < t_max && closest.is_none_or(|(best, _, _)| t < best) {
+                closest = Some((t, normal, uv));
+            }
+        };
+
+        let a = d.x() * d.x() + d.z() * d.z() - k2 * d.y() * d.y();
+        let b = 2.0 * (o.x() * d.x() + o.z() * d.z() - k2 * o.y() * d.y());
+        let c = o.x() * o.x() + o.z() * o.z() - k2 * o.y() * o.y();
+        if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+            for t in [t0, t1] {
+                let p = o + t * d;
+                if (-self.height..=0.0).contains(&p.y()) {
+                    let phi = Float::atan2(-p.z(), p.x()) + PI;
+                    let normal = Vec3::new(p.x(), -k2 * p.y(), p.z()).unit_vec();
+                    consider(t, normal, (phi / (2.0 * PI), 1.0 + p.y() / self.height));
+                }
+            }
+        }
+
+        if self.capped && d.y() != 0.0 {
+            let t = (-self.height - o.y()) / d.y();
+            let p = o + t * d;
+            if p.x() * p.x() + p.z() * p.z() <= self.radius * self.radius {
+                let uv = (0.5 + 0.5 * p.x() / self.radius, 0.5 + 0.5 * p.z() / self.radius);
+                consider(t, Vec3::new(0.0, -1.0, 0.0), uv);
+            }
+        }
+
+        let (t, outward_normal, uv) = closest?;
+        let mut rec = HitRecord::new(ray.at(t), outward_normal, t, uv, self.material.clone());
+        rec.set_face_normal(ray, &outward_normal);
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.base - Vec3::new(self.radius, 0.0, self.radius),
+            self.base + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn export(&self, scene: &mut SceneWriter) -> Option<()> {
+        let material = scene.material(&self.material)?;
+        scene.entry(format!("cone {} {} {} {} {}", scene_file::point(self.base), self.radius, self.height, if self.capped { "capped" } else { "open" }, material));
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+
+    fn cone() -> Cone {
+        Cone::new(Point3::new(0.0, 0.0, 0.0), 1.0, 1.0, true, Rc::new(Lambertian::new(Color::default())))
+    }
+
+    #[test]
+    fn side_hit_normal_is_tilted_outward() {
+        let ray = Ray::new(Point3::new(5.0, 0.5, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let rec = cone().hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 4.5).abs() < 1e-5);
+        let expected = Float::sqrt(0.5);
+        assert!((rec.normal.x() - expected).abs() < 1e-5);
+        assert!((rec.normal.y() - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn ray_above_apex_misses_upper_nappe() {
+        let ray = Ray::new(Point3::new(5.0, 1.5, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(cone().hit(&ray, 0.0, Float::INFINITY).is_none());
+    }
+
+    #[test]
+    fn base_cap_faces_down() {
+        let ray = Ray::new(Point3::new(0.1, -2.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let rec = cone().hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 2.0).abs() < 1e-6);
+        assert_eq!(-1.0, rec.normal.y());
+        assert!(rec.front_face);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::float::Float;
+use crate::hit::{Hit, HitRecord};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub(crate) struct Crossing {
+    pub t: Float,
+    pub normal: Vec3,
+    pub uv: (Float, Float),
+    pub material: Rc<dyn Material>,
+}
+
+impl Crossing {
+    pub fn new(t: Float, normal: Vec3, uv: (Float, Float), material: Rc<dyn Material>) -> Self {
+        Crossing { t, normal, uv, material }
+    }
+
+    fn flipped(self) -> Self {
+        Crossing { normal: -self.normal, ..self }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Span {
+    pub enter: Crossing,
+    pub exit: Crossing,
+}
+
+/// A closed object that can report every interval of the ray inside it, sorted along the ray.
+pub(crate) trait Solid: Hit {
+    fn spans(&self, ray: &Ray) -> Vec<Span>;
+}
+
+pub(crate) fn first_crossing(spans: &[Span], ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+    let crossing = spans
+        .iter()
+        .flat_map(|span| vec![&span.enter, &span.exit])
+        .find(|crossing| crossing.t > t_min && crossing.t < t_max)?;
+
+    let mut rec = HitRecord::new(ray.at(crossing.t), crossing.normal, crossing.t, crossing.uv, crossing.material.clone());
+    rec.set_face_normal(ray, &crossing.normal);
+    Some(rec)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum CsgOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOp {
+    fn inside(self, left: bool, right: bool) -> bool {
+        match self {
+            CsgOp::Union => left || right,
+            CsgOp::Intersection => left && right,
+            CsgOp::Difference => left && !right,
+        }
+    }
+}
+
+pub(crate) struct Csg {
+    op: CsgOp,
+    left: Rc<dyn Solid>,
+    right: Rc<dyn Solid>,
+}
+
+impl Csg {
+    pub fn new(op: CsgOp, left: Rc<dyn Solid>, right: Rc<dyn Solid>) -> Self {
+        Csg { op, left, right }
+    }
+}
+
+impl Solid for Csg {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let mut events: Vec<(bool, bool, Crossing)> = Vec::new();
+        for (is_left, spans) in [(true, self.left.spans(ray)), (false, self.right.spans(ray))] {
+            for span in spans {
+                events.push((is_left, true, span.enter));
+                events.push((is_left, false, span.exit));
+            }
+        }
+        events.sort_by(|a, b| a.2.t.partial_cmp(&b.2.t).unwrap_or(Ordering::Equal));
+
+        let (mut in_left, mut in_right, mut inside) = (false, false, false);
+        let mut enter = None;
+        let mut spans = Vec::new();
+        for (is_left, entering, crossing) in events {
+            if is_left {
+                in_left = entering;
+            } else {
+                in_right = entering;
+            }
+
+            let now_inside = self.op.inside(in_left, in_right);
+            if now_inside == inside {
+                continue;
+            }
+            inside = now_inside;
+
+            let crossing = if !is_left && self.op == CsgOp::Difference { crossing.flipped() } else { crossing };
+            match enter.take() {
+                None => enter = Some(crossing),
+                Some(enter) => spans.push(Span { enter, exit: crossing }),
+            }
+        }
+        spans
+    }
+}
+
+impl Hit for Csg {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        first_crossing(&self.spans(ray), ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let (left, right) = (self.left.bounding_box(), self.right.bounding_box());
+        match self.op {
+            CsgOp::Union => Some(left?.surrounding(&right?)),
+            CsgOp::Intersection => match (left, right) {
+                (Some(left), Some(right)) => Some(left.overlap(&right)),
+                (left, right) => left.or(right),
+            },
+            CsgOp::Difference => left,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::point3::Point3;
+    use crate::sphere::Sphere;
+
+    fn sphere(x: Float) -> Rc<dyn Solid> {
+        Rc::new(Sphere::new(Point3::new(x, 0.0, 0.0), 1.0, Rc::new(Lambertian::new(Color::default()))))
+    }
+
+    fn intervals(csg: &Csg) -> Vec<(Float, Float)> {
+        let ray = Ray::new(Point3::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        csg.spans(&ray)
+            .iter()
+            .map(|span| (span.enter.t - 10.0, span.exit.t - 10.0))
+            .collect()
+    }
+
+    #[test]
+    fn union_merges_overlapping_spans() {
+        let csg = Csg::new(CsgOp::Union, sphere(0.0), sphere(1.5));
+        assert_eq!(vec![(-1.0, 2.5)], intervals(&csg));
+
+        let csg = Csg::new(CsgOp::Union, sphere(0.0), sphere(3.0));
+        assert_eq!(vec![(-1.0, 1.0), (2.0, 4.0)], intervals(&csg));
+    }
+
+    #[test]
+    fn intersection_keeps_overlap() {
+        let csg = Csg::new(CsgOp::Intersection, sphere(0.0), sphere(1.5));
+        assert_eq!(vec![(0.5, 1.0)], intervals(&csg));
+
+        let csg = Csg::new(CsgOp::Intersection, sphere(0.0), sphere(3.0));
+        assert!(intervals(&csg).is_empty());
+    }
+
+    #[test]
+    fn difference_flips_carved_normals() {
+        let csg = Csg::new(CsgOp::Difference, sphere(0.0), sphere(1.5));
+        assert_eq!(vec![(-1.0, 0.5)], intervals(&csg));
+
+        let ray = Ray::new(Point3::new(10.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let rec = csg.hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 9.5).abs() < 1e-5);
+        assert!(rec.front_face);
+        assert!((rec.normal.x() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn nested_difference_splits_span() {
+        let shell = Csg::new(CsgOp::Difference, sphere(0.0), Rc::new(Sphere::new(
+            Point3::new(0.0, 0.0, 0.0),
+            0.5,
+            Rc::new(Lambertian::new(Color::default())),
+        )));
+        assert_eq!(vec![(-1.0, -0.5), (0.5, 1.0)], intervals(&shell));
+
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = shell.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((rec.t - 0.5).abs() < 1e-5);
+        assert!(rec.front_face);
+    }
+}
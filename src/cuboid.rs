@@ -0,0 +1,126 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::hit::{Hit, HitRecord};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::csg::{first_crossing, Crossing, Solid, Span};
+use crate::scene_file::{self, SceneWriter};
+use std::rc::Rc;
+
+pub(crate) struct Cuboid {
+    bounds: Aabb,
+    material: Rc<dyn Material>,
+}
+
+impl Cuboid {
+    /// Axis-aligned box spanning the two opposite corners `a` and `b`.
+    pub fn new(a: Point3, b: Point3, material: Rc<dyn Material>) -> Self {
+        Cuboid { bounds: Aabb::new(a, b), material }
+    }
+
+    fn crossing(&self, ray: &Ray, t: Float, axis: u8, sign: Float) -> Crossing {
+        let mut normal = [0.0; 3];
+        normal[axis as usize] = sign;
+
+        let p = ray.at(t);
+        let (i, j) = ((axis + 1) % 3, (axis + 2) % 3);
+        let coord = |k: u8| (p[k] - self.bounds.min[k]) / (self.bounds.max[k] - self.bounds.min[k]);
+        let normal = Vec3::new(normal[0], normal[1], normal[2]);
+        Crossing::new(t, normal, (coord(i), coord(j)), self.material.clone())
+    }
+}
+
+impl Solid for Cuboid {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let (mut t_enter, mut t_exit) = (Float::NEG_INFINITY, Float::INFINITY);
+        let (mut enter_face, mut exit_face) = ((0, -1.0), (0, 1.0));
+
+        for axis in 0..3 {
+            let (o, d) = (ray.origin[axis], ray.direction[axis]);
+            if d == 0.0 {
+                if o < self.bounds.min[axis] || o > self.bounds.max[axis] {
+                    return Vec::new();
+                }
+                continue;
+            }
+
+            let (near, far, sign) = if d > 0.0 {
+                (self.bounds.min[axis], self.bounds.max[axis], 1.0)
+            } else {
+                (self.bounds.max[axis], self.bounds.min[axis], -1.0)
+            };
+            let (t0, t1) = ((near - o) / d, (far - o) / d);
+            if t0 > t_enter {
+                t_enter = t0;
+                enter_face = (axis, -sign);
+            }
+            if t1 < t_exit {
+                t_exit = t1;
+                exit_face = (axis, sign);
+            }
+        }
+
+        if t_enter >= t_exit {
+            return Vec::new();
+        }
+        vec![Span {
+            enter: self.crossing(ray, t_enter, enter_face.0, enter_face.1),
+            exit: self.crossing(ray, t_exit, exit_face.0, exit_face.1),
+        }]
+    }
+}
+
+impl Hit for Cuboid {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        first_crossing(&self.spans(ray), ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+
+    fn export(&self, scene: &mut SceneWriter) -> Option<()> {
+        let material = scene.material(&self.material)?;
+        scene.entry(format!("cuboid {} {} {}", scene_file::point(self.bounds.min), scene_file::point(self.bounds.max), material));
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+
+    fn cuboid() -> Cuboid {
+        Cuboid::new(Point3::new(1.0, 1.0, 1.0), Point3::new(-1.0, 0.0, -1.0), Rc::new(Lambertian::new(Color::default())))
+    }
+
+    #[test]
+    fn front_face_normal_and_uv() {
+        let ray = Ray::new(Point3::new(0.5, 0.25, 5.0), Vec3::new(0.0, 0.0, -2.0));
+        let rec = cuboid().hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 2.0).abs() < 1e-6);
+        assert_eq!(1.0, rec.normal.z());
+        assert!(rec.front_face);
+        assert!((rec.u - 0.75).abs() < 1e-6);
+        assert!((rec.v - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn inside_hits_back_face() {
+        let ray = Ray::new(Point3::new(0.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = cuboid().hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((rec.t - 1.0).abs() < 1e-6);
+        assert!(!rec.front_face);
+        assert_eq!(-1.0, rec.normal.x());
+    }
+
+    #[test]
+    fn parallel_ray_outside_slab_misses() {
+        let ray = Ray::new(Point3::new(-5.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(cuboid().spans(&ray).is_empty());
+    }
+}
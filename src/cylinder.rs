@@ -0,0 +1,199 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::hit::{Hit, HitRecord};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::csg::{Crossing, Solid, Span};
+use crate::roots::solve_quadratic;
+use crate::scene_file::{self, SceneWriter};
+use crate::float::consts::PI;
+use std::rc::Rc;
+
+pub(crate) struct Cylinder {
+    base: Point3,
+    radius: Float,
+    height: Float,
+    capped: bool,
+    material: Rc<dyn Material>,
+}
+
+impl Cylinder {
+    /// Vertical cylinder standing on `base`, optionally closed with disks at both ends.
+    pub fn new(base: Point3, radius: Float, height: Float, capped: bool, material: Rc<dyn Material>) -> Self {
+        Cylinder { base, radius, height, capped, material }
+    }
+}
+
+impl Hit for Cylinder {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let o = Vec3::from(ray.origin) - Vec3::from(self.base);
+        let d = ray.direction;
+        let mut closest: Option<(Float, Vec3, (Float, Float))> = None;
+        let mut consider = |t: Float, normal: Vec3, uv: (Float, Float)| {
+            if t > t_min && t < t_max && closest.is_none_or(|(best, _, _)| t < best) {
+                closest = Some((t, normal, uv));
+            }
+        };
+
+        let a = d.x() * d.x() + d.z() * d.z();
+        let b = 2.0 * (o.x() * d.x() + o.z() * d.z());
+        let c = o.x() * o.x() + o.z() * o.z() - self.radius * self.radius;
+        if a > 0.0 {
+            if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+                for t in [t0, t1] {
+                    let p = o + t * d;
+                    if (0.0..=self.height).contains(&p.y()) {
+                        let phi = Float::atan2(-p.z(), p.x()) + PI;
+                        let normal = Vec3::new(p.x() / self.radius, 0.0, p.z() / self.radius);
+                        consider(t, normal, (phi / (2.0 * PI), p.y() / self.height));
+                    }
+                }
+            }
+        }
+
+        if self.capped && d.y() != 0.0 {
+            for (y, normal_y) in [(0.0, -1.0), (self.height, 1.0)] {
+                let t = (y - o.y()) / d.y();
+                let p = o + t * d;
+                if p.x() * p.x() + p.z() * p.z() <= self.radius * self.radius {
+                    let uv = (0.5 + 0.5 * p.x() / self.radius, 0.5 + 0.5 * p.z() / self.radius);
+                    consider(t, Vec3::new(0.0, normal_y, 0.0), uv);
+                }
+            }
+        }
+
+        let (t, outward_normal, uv) = closest?;
+        let mut rec = HitRecord::new(ray.at(t), outward_normal, t, uv, self.material.clone());
+        rec.set_face_normal(ray, &outward_normal);
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.base - Vec3::new(self.radius, 0.0, self.radius),
+            self.base + Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn export(&self, scene: &mut SceneWriter) -> Option<()> {
+        let material = scene.material(&self.material)?;
+        scene.entry(format!("cylinder {} {} {} {} {}", scene_file::point(self.base), self.radius, self.height, if self.capped { "capped" } else { "open" }, material));
+        Some(())
+    }
+}
+
+/// The solid is always treated as capped, since an open tube has no inside.
+impl Solid for Cylinder {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let o = Vec3::from(ray.origin) - Vec3::from(self.base);
+        let d = ray.direction;
+
+        let (mut t_enter, mut t_exit) = if d.y() != 0.0 {
+            let (t0, t1) = (-o.y() / d.y(), (self.height - o.y()) / d.y());
+            (t0.min(t1), t0.max(t1))
+        } else if (0.0..=self.height).contains(&o.y()) {
+            (Float::NEG_INFINITY, Float::INFINITY)
+        } else {
+            return Vec::new();
+        };
+        let (mut side_enter, mut side_exit) = (false, false);
+
+        let a = d.x() * d.x() + d.z() * d.z();
+        let b = 2.0 * (o.x() * d.x() + o.z() * d.z());
+        let c = o.x() * o.x() + o.z() * o.z() - self.radius * self.radius;
+        if a > 0.0 {
+            let (t0, t1) = match solve_quadratic(a, b, c) {
+                Some(roots) => roots,
+                None => return Vec::new(),
+            };
+            if t0 > t_enter {
+                t_enter = t0;
+                side_enter = true;
+            }
+            if t1 < t_exit {
+                t_exit = t1;
+                side_exit = true;
+            }
+        } else if c > 0.0 {
+            return Vec::new();
+        }
+        if t_enter >= t_exit {
+            return Vec::new();
+        }
+
+        let crossing = |t: Float, side: bool| {
+            let p = o + t * d;
+            if side {
+                let phi = Float::atan2(-p.z(), p.x()) + PI;
+                let normal = Vec3::new(p.x() / self.radius, 0.0, p.z() / self.radius);
+                Crossing::new(t, normal, (phi / (2.0 * PI), p.y() / self.height), self.material.clone())
+            } else {
+                let normal_y = if p.y() > 0.5 * self.height { 1.0 } else { -1.0 };
+                let uv = (0.5 + 0.5 * p.x() / self.radius, 0.5 + 0.5 * p.z() / self.radius);
+                Crossing::new(t, Vec3::new(0.0, normal_y, 0.0), uv, self.material.clone())
+            }
+        };
+        vec![Span { enter: crossing(t_enter, side_enter), exit: crossing(t_exit, side_exit) }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+
+    fn cylinder(capped: bool) -> Cylinder {
+        Cylinder::new(Point3::new(0.0, 0.0, 0.0), 1.0, 2.0, capped, Rc::new(Lambertian::new(Color::default())))
+    }
+
+    #[test]
+    fn side_hit_has_radial_normal() {
+        let ray = Ray::new(Point3::new(5.0, 1.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let rec = cylinder(false).hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 4.0).abs() < 1e-6);
+        assert!((rec.normal.x() - 1.0).abs() < 1e-6);
+        assert!((rec.v - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn caps_close_the_cylinder() {
+        let ray = Ray::new(Point3::new(0.2, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = cylinder(true).hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 3.0).abs() < 1e-6);
+        assert_eq!(1.0, rec.normal.y());
+
+        let rec = cylinder(false).hit(&ray, 0.0, Float::INFINITY);
+        assert!(rec.is_none());
+    }
+
+    #[test]
+    fn uncapped_inside_is_back_face() {
+        let ray = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let rec = cylinder(false).hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 1.0).abs() < 1e-6);
+        assert!(!rec.front_face);
+        assert!((rec.normal.z() + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn solid_span_enters_side_and_leaves_through_cap() {
+        let ray = Ray::new(Point3::new(-2.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        let spans = cylinder(false).spans(&ray);
+        assert_eq!(1, spans.len());
+        assert!((spans[0].enter.t - 1.0).abs() < 1e-6);
+        assert!((spans[0].enter.normal.x() + 1.0).abs() < 1e-6);
+        assert!((spans[0].exit.t - 2.0).abs() < 1e-6);
+        assert_eq!(1.0, spans[0].exit.normal.y());
+    }
+
+    #[test]
+    fn cylinder_bounding_box() {
+        let bbox = cylinder(true).bounding_box().unwrap();
+        assert_eq!(Point3::new(-1.0, 0.0, -1.0), bbox.min);
+        assert_eq!(Point3::new(1.0, 2.0, 1.0), bbox.max);
+    }
+}
+
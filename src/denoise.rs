@@ -0,0 +1,135 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::vec3::Vec3;
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Denoiser {
+    pub radius: i32,
+    pub patch_radius: i32,
+    pub sigma_color: Float,
+    pub sigma_albedo: Float,
+    pub sigma_normal: Float,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Denoiser {
+            radius: 5,
+            patch_radius: 1,
+            sigma_color: 0.15,
+            sigma_albedo: 0.1,
+            sigma_normal: 0.3,
+        }
+    }
+}
+
+impl Denoiser {
+    /// Non-local means over the beauty image, with the weights of each neighbor additionally
+    /// guided by how similar its albedo and normal are, so texture and geometry edges survive.
+    pub fn denoise(&self, width: u32, height: u32, color: &[Color], albedo: &[Color], normal: &[Color]) -> Vec<Color> {
+        let (width, height) = (width as i32, height as i32);
+        let compressed: Vec<Vec3> = color
+            .iter()
+            .map(|c| Vec3::new(compress(c.r()), compress(c.g()), compress(c.b())))
+            .collect();
+        let at = |x: i32, y: i32| (y.clamp(0, height - 1) * width + x.clamp(0, width - 1)) as usize;
+
+        let patch_size = ((2 * self.patch_radius + 1) * (2 * self.patch_radius + 1)) as Float;
+        let inv_color = 1.0 / (3.0 * patch_size * self.sigma_color * self.sigma_color);
+        let inv_albedo = 1.0 / (self.sigma_albedo * self.sigma_albedo);
+        let inv_normal = 1.0 / (self.sigma_normal * self.sigma_normal);
+        let inv_spatial = 2.0 / (self.radius * self.radius).max(1) as Float;
+
+        let mut result = Vec::with_capacity(color.len());
+        for y in 0..height {
+            for x in 0..width {
+                let p = at(x, y);
+                let mut sum = Vec3::default();
+                let mut weight_sum = 0.0;
+
+                for dy in -self.radius..=self.radius {
+                    for dx in -self.radius..=self.radius {
+                        let (qx, qy) = (x + dx, y + dy);
+                        if qx < 0 || qy < 0 || qx >= width || qy >= height {
+                            continue;
+                        }
+                        let q = at(qx, qy);
+
+                        let mut patch_distance = 0.0;
+                        for py in -self.patch_radius..=self.patch_radius {
+                            for px in -self.patch_radius..=self.patch_radius {
+                                let diff = compressed[at(x + px, y + py)] - compressed[at(qx + px, qy + py)];
+                                patch_distance += diff.length_squared();
+                            }
+                        }
+                        let albedo_distance = (albedo[p].0 - albedo[q].0).length_squared();
+                        let normal_distance = (normal[p].0 - normal[q].0).length_squared();
+
+                        let weight = Float::exp(
+                            -((dx * dx + dy * dy) as Float * inv_spatial)
+                                - patch_distance * inv_color
+                                - albedo_distance * inv_albedo
+                                - normal_distance * inv_normal,
+                        );
+                        sum += weight * color[q].0;
+                        weight_sum += weight;
+                    }
+                }
+
+                result.push((sum / weight_sum).into());
+            }
+        }
+
+        result
+    }
+}
+
+fn compress(c: Float) -> Float {
+    let c = c.max(0.0);
+    c / (1.0 + c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random;
+    use rand::Rng;
+
+    const SIZE: u32 = 16;
+
+    fn variance(pixels: &[Color]) -> Float {
+        let mean = pixels.iter().map(|p| p.r()).sum::<Float>() / pixels.len() as Float;
+        pixels.iter().map(|p| (p.r() - mean).powi(2)).sum::<Float>() / pixels.len() as Float
+    }
+
+    #[test]
+    fn flat_region_noise_is_reduced() {
+        random::seed(3);
+        let noisy: Vec<Color> = (0..SIZE * SIZE)
+            .map(|_| {
+                let v = random::rng().gen_range(0.3, 0.7);
+                Color::new(v, v, v)
+            })
+            .collect();
+        let albedo = vec![Color::new(0.5, 0.5, 0.5); noisy.len()];
+        let normal = vec![Color::new(0.0, 1.0, 0.0); noisy.len()];
+
+        let denoised = Denoiser::default().denoise(SIZE, SIZE, &noisy, &albedo, &normal);
+        assert!(variance(&denoised) < 0.25 * variance(&noisy));
+    }
+
+    #[test]
+    fn albedo_edges_are_preserved() {
+        let split = |left: Color, right: Color| -> Vec<Color> {
+            (0..SIZE * SIZE).map(|i| if i % SIZE < SIZE / 2 { left } else { right }).collect()
+        };
+        let color = split(Color::new(0.1, 0.1, 0.1), Color::new(0.9, 0.9, 0.9));
+        let albedo = split(Color::new(0.1, 0.1, 0.1), Color::new(0.9, 0.9, 0.9));
+        let normal = vec![Color::new(0.0, 0.0, 1.0); color.len()];
+
+        let denoised = Denoiser::default().denoise(SIZE, SIZE, &color, &albedo, &normal);
+        let row = (SIZE * SIZE / 2) as usize;
+        assert!((denoised[row + SIZE as usize / 2 - 1].r() - 0.1).abs() < 1e-3);
+        assert!((denoised[row + SIZE as usize / 2].r() - 0.9).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::hit::{Hit, HitRecord};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::plane::{plane_intersection, tangent_basis};
+use crate::scene_file::{self, SceneWriter};
+use crate::float::consts::PI;
+use std::rc::Rc;
+
+pub(crate) struct Disk {
+    center: Point3,
+    normal: Vec3,
+    radius: Float,
+    tangent: Vec3,
+    bitangent: Vec3,
+    material: Rc<dyn Material>,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: Float, material: Rc<dyn Material>) -> Self {
+        let normal = normal.unit_vec();
+        let (tangent, bitangent) = tangent_basis(&normal);
+        Disk { center, normal, radius, tangent, bitangent, material }
+    }
+}
+
+impl Hit for Disk {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let t = plane_intersection(ray, self.center, &self.normal)?;
+        if t <= t_min || t >= t_max {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let offset = Vec3::from(p) - Vec3::from(self.center);
+        let distance = offset.length();
+        if distance > self.radius {
+            return None;
+        }
+
+        let phi = Float::atan2(Vec3::dot(&offset, &self.bitangent), Vec3::dot(&offset, &self.tangent));
+        let uv = ((phi + PI) / (2.0 * PI), distance / self.radius);
+
+        let mut rec = HitRecord::new(p, self.normal, t, uv, self.material.clone());
+        rec.set_face_normal(ray, &self.normal);
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let n = self.normal;
+        let extent = |c: Float| self.radius * (1.0 - c * c).max(0.0).sqrt();
+        let half = Vec3::new(extent(n.x()), extent(n.y()), extent(n.z()));
+        Some(Aabb::new(self.center - half, self.center + half))
+    }
+
+    fn export(&self, scene: &mut SceneWriter) -> Option<()> {
+        let material = scene.material(&self.material)?;
+        scene.entry(format!("disk {} {} {} {}", scene_file::point(self.center), scene_file::vector(self.normal), self.radius, material));
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+
+    fn disk() -> Disk {
+        Disk::new(Point3::new(0.0, 0.0, -2.0), Vec3::new(0.0, 0.0, 1.0), 1.0, Rc::new(Lambertian::new(Color::default())))
+    }
+
+    #[test]
+    fn disk_hit_inside_radius_only() {
+        let rec = disk().hit(&Ray::new(Point3::new(0.5, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0)), 0.0, Float::INFINITY).unwrap();
+        assert_eq!(2.0, rec.t);
+        assert_eq!(1.0, rec.normal.z());
+        assert_eq!(0.5, rec.v);
+
+        assert!(disk().hit(&Ray::new(Point3::new(1.5, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0)), 0.0, Float::INFINITY).is_none());
+    }
+
+    #[test]
+    fn disk_bounding_box_is_flat() {
+        let bbox = disk().bounding_box().unwrap();
+        assert_eq!(Point3::new(-1.0, -1.0, -2.0), bbox.min);
+        assert_eq!(Point3::new(1.0, 1.0, -2.0), bbox.max);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::camera::{Camera, orthonormal_basis};
+use crate::float::consts::PI;
+
+pub(crate) struct EquirectangularCamera {
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl EquirectangularCamera {
+    pub fn new(origin: Point3, destination: Point3, view_up: Vec3) -> Self {
+        let (u, v, w) = orthonormal_basis(origin, destination, view_up);
+        EquirectangularCamera { origin, u, v, w }
+    }
+}
+
+impl Camera for EquirectangularCamera {
+    fn get_ray(&self, u: Float, v: Float, _lens_sample: (Float, Float)) -> Option<Ray> {
+        let longitude = (u - 0.5) * 2.0 * PI;
+        let latitude = (v - 0.5) * PI;
+
+        Some(Ray::new(
+            self.origin,
+            latitude.cos() * (longitude.sin() * self.u - longitude.cos() * self.w) + latitude.sin() * self.v,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Projection;
+
+    fn camera() -> EquirectangularCamera {
+        EquirectangularCamera::new(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0))
+    }
+
+    #[test]
+    fn covers_full_sphere() {
+        let camera = camera();
+
+        let forward = camera.get_ray(0.5, 0.5, (0.5, 0.5)).unwrap().direction;
+        assert!((forward.z() + 1.0).abs() < 1e-6);
+
+        let backward = camera.get_ray(0.0, 0.5, (0.5, 0.5)).unwrap().direction;
+        assert!((backward.z() - 1.0).abs() < 1e-6);
+
+        let right = camera.get_ray(0.75, 0.5, (0.5, 0.5)).unwrap().direction;
+        assert!((right.x() - 1.0).abs() < 1e-6);
+
+        let up = camera.get_ray(0.5, 1.0, (0.5, 0.5)).unwrap().direction;
+        assert!((up.y() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn image_edges_map_to_longitude_and_latitude_limits() {
+        let camera = camera();
+        let (width, height) = crate::image_size(Projection::Equirectangular);
+        assert_eq!(width, 2 * height);
+
+        let (w, h) = (width as Float, height as Float);
+        let degrees = |x: Float, y: Float| {
+            let (u, v) = crate::film_to_screen((x, y), (width, height));
+            let d = camera.get_ray(u, v, (0.5, 0.5)).unwrap().direction.unit_vec();
+            (Float::atan2(d.x(), -d.z()).to_degrees(), d.y().asin().to_degrees())
+        };
+
+        for (x, longitude) in [(0.25 * w, -90.0), (0.5 * w, 0.0), (0.75 * w, 90.0)] {
+            let (lon, lat) = degrees(x, 0.5 * h);
+            assert!((lon - longitude).abs() < 1e-3 && lat.abs() < 1e-3);
+        }
+        // Longitude is meaningless at the poles.
+        assert!((degrees(0.5 * w, 0.0).1 + 90.0).abs() < 1e-3);
+        assert!((degrees(0.5 * w, h).1 - 90.0).abs() < 1e-3);
+
+        // Both side edges look straight back, from either side of the seam.
+        for x in [0.0, w] {
+            assert!((degrees(x, 0.5 * h).0.abs() - 180.0).abs() < 1e-3);
+        }
+        assert!((degrees(0.01, 0.5 * h).0 + 180.0).abs() < 0.01);
+        assert!((degrees(w - 0.01, 0.5 * h).0 - 180.0).abs() < 0.01);
+    }
+}
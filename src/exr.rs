@@ -0,0 +1,218 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum ExrPrecision {
+    #[default]
+    Half,
+    Float,
+}
+
+impl FromStr for ExrPrecision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "half" => Ok(ExrPrecision::Half),
+            "float" => Ok(ExrPrecision::Float),
+            _ => Err(format!("unknown EXR precision `{}`, expected one of: half, float", s)),
+        }
+    }
+}
+
+impl ExrPrecision {
+    fn pixel_type(self) -> i32 {
+        match self {
+            ExrPrecision::Half => 1,
+            ExrPrecision::Float => 2,
+        }
+    }
+
+    fn bytes(self) -> usize {
+        match self {
+            ExrPrecision::Half => 2,
+            ExrPrecision::Float => 4,
+        }
+    }
+}
+
+pub(crate) struct ExrChannel {
+    pub name: String,
+    pub values: Vec<f32>,
+    pub precision: ExrPrecision,
+}
+
+impl ExrChannel {
+    pub fn new(name: &str, values: Vec<f32>, precision: ExrPrecision) -> Self {
+        ExrChannel { name: name.to_string(), values, precision }
+    }
+}
+
+pub(crate) fn write_exr(
+    out: &mut impl Write,
+    width: u32,
+    height: u32,
+    channels: &mut [ExrChannel],
+) -> io::Result<()> {
+    if let Some(channel) = channels.iter().find(|c| c.values.len() != (width * height) as usize) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("EXR channel `{}` has {} values, expected {}", channel.name, channel.values.len(), width * height),
+        ));
+    }
+    channels.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&0x0131_2f76u32.to_le_bytes());
+    header.extend_from_slice(&2u32.to_le_bytes());
+
+    let mut chlist = Vec::new();
+    for channel in channels.iter() {
+        chlist.extend_from_slice(channel.name.as_bytes());
+        chlist.push(0);
+        chlist.extend_from_slice(&channel.precision.pixel_type().to_le_bytes());
+        chlist.extend_from_slice(&[0, 0, 0, 0]);
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+    }
+    chlist.push(0);
+
+    let window: Vec<u8> = [0, 0, width as i32 - 1, height as i32 - 1]
+        .iter()
+        .flat_map(|v| v.to_le_bytes().to_vec())
+        .collect();
+
+    write_attribute(&mut header, "channels", "chlist", &chlist);
+    write_attribute(&mut header, "compression", "compression", &[0]);
+    write_attribute(&mut header, "dataWindow", "box2i", &window);
+    write_attribute(&mut header, "displayWindow", "box2i", &window);
+    write_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    write_attribute(&mut header, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+    write_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    write_attribute(&mut header, "screenWindowWidth", "float", &1f32.to_le_bytes());
+    header.push(0);
+
+    let line_size = width as usize * channels.iter().map(|c| c.precision.bytes()).sum::<usize>();
+    let first_line = header.len() + 8 * height as usize;
+    for y in 0..height as usize {
+        header.extend_from_slice(&((first_line + y * (line_size + 8)) as u64).to_le_bytes());
+    }
+    out.write_all(&header)?;
+
+    let mut line = Vec::with_capacity(line_size);
+    for y in 0..height as usize {
+        line.clear();
+        for channel in channels.iter() {
+            for value in &channel.values[y * width as usize..(y + 1) * width as usize] {
+                match channel.precision {
+                    ExrPrecision::Half => line.extend_from_slice(&f32_to_half(*value).to_le_bytes()),
+                    ExrPrecision::Float => line.extend_from_slice(&value.to_le_bytes()),
+                }
+            }
+        }
+        out.write_all(&(y as i32).to_le_bytes())?;
+        out.write_all(&(line.len() as i32).to_le_bytes())?;
+        out.write_all(&line)?;
+    }
+
+    Ok(())
+}
+
+fn write_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+fn f32_to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        return sign | 0x7c00 | if mantissa != 0 { 0x0200 } else { 0 };
+    }
+
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - half_exponent) as u32;
+        let half_mantissa = mantissa >> shift;
+        let remainder = mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round = remainder > halfway || (remainder == halfway && half_mantissa & 1 == 1);
+        return sign | (half_mantissa + round as u32) as u16;
+    }
+
+    let half = ((half_exponent as u32) << 10) | (mantissa >> 13);
+    let remainder = mantissa & 0x1fff;
+    let round = remainder > 0x1000 || (remainder == 0x1000 && half & 1 == 1);
+    sign | (half + round as u32) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn half_conversion() {
+        assert_eq!(0x0000, f32_to_half(0.0));
+        assert_eq!(0x3c00, f32_to_half(1.0));
+        assert_eq!(0xc000, f32_to_half(-2.0));
+        assert_eq!(0x3555, f32_to_half(1.0 / 3.0));
+        assert_eq!(0x7bff, f32_to_half(65504.0));
+        assert_eq!(0x7c00, f32_to_half(1e6));
+        assert_eq!(0x0001, f32_to_half(5.96e-8));
+    }
+
+    #[test]
+    fn exr_layout() {
+        let mut exr = Vec::new();
+        let mut channels = vec![
+            ExrChannel::new("R", vec![1.0, 2.0], ExrPrecision::Float),
+            ExrChannel::new("B", vec![0.0, 0.5], ExrPrecision::Float),
+        ];
+        write_exr(&mut exr, 2, 1, &mut channels).unwrap();
+
+        assert_eq!(&[0x76, 0x2f, 0x31, 0x01], &exr[..4]);
+        assert_eq!("B", channels[0].name);
+
+        let offset = u64::from_le_bytes(exr[exr.len() - 32..exr.len() - 24].try_into().unwrap()) as usize;
+        assert_eq!(exr.len() - 24, offset);
+        assert_eq!(16, i32::from_le_bytes(exr[offset + 4..offset + 8].try_into().unwrap()));
+        assert_eq!(0.5, f32::from_le_bytes(exr[offset + 12..offset + 16].try_into().unwrap()));
+    }
+
+    #[test]
+    fn mismatched_channel_is_rejected() {
+        let mut channels = vec![ExrChannel::new("R", vec![1.0], ExrPrecision::Half)];
+        assert!(write_exr(&mut Vec::new(), 2, 1, &mut channels).is_err());
+    }
+
+    #[test]
+    fn channels_mix_precisions() {
+        let mut exr = Vec::new();
+        let mut channels = vec![
+            ExrChannel::new("R", vec![1.0, 2.0], ExrPrecision::Half),
+            ExrChannel::new("objectId", vec![3.0, 70000.0], ExrPrecision::Float),
+        ];
+        write_exr(&mut exr, 2, 1, &mut channels).unwrap();
+
+        let offset = u64::from_le_bytes(exr[exr.len() - 28..exr.len() - 20].try_into().unwrap()) as usize;
+        assert_eq!(exr.len() - 20, offset);
+        assert_eq!(12, i32::from_le_bytes(exr[offset + 4..offset + 8].try_into().unwrap()));
+        assert_eq!(0x3c00, u16::from_le_bytes(exr[offset + 8..offset + 10].try_into().unwrap()));
+        assert_eq!(70000.0, f32::from_le_bytes(exr[offset + 16..offset + 20].try_into().unwrap()));
+    }
+}
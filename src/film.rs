@@ -0,0 +1,127 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::vec3::Vec3;
+use crate::filter::Filter;
+
+pub(crate) struct Film {
+    width: u32,
+    height: u32,
+    filter: Box<dyn Filter>,
+    sums: Vec<Vec3>,
+    weights: Vec<Float>,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32, filter: Box<dyn Filter>) -> Self {
+        let size = (width * height) as usize;
+        Film {
+            width,
+            height,
+            filter,
+            sums: vec![Vec3::default(); size],
+            weights: vec![0.0; size],
+        }
+    }
+
+    pub fn add_sample(&mut self, x: Float, y: Float, color: Color) {
+        let radius = self.filter.radius();
+        let x0 = i64::max(0, (x - 0.5 - radius).ceil() as i64);
+        let x1 = i64::min(self.width as i64 - 1, (x - 0.5 + radius).floor() as i64);
+        let y0 = i64::max(0, (y - 0.5 - radius).ceil() as i64);
+        let y1 = i64::min(self.height as i64 - 1, (y - 0.5 + radius).floor() as i64);
+
+        for py in y0..=y1 {
+            for px in x0..=x1 {
+                let weight = self.filter.evaluate(px as Float + 0.5 - x, py as Float + 0.5 - y);
+                if weight != 0.0 {
+                    let idx = self.index(px as u32, py as u32);
+                    self.sums[idx] += weight * Vec3::from(color);
+                    self.weights[idx] += weight;
+                }
+            }
+        }
+    }
+
+    pub fn resolve(&self) -> Vec<Color> {
+        (0..self.height)
+            .rev()
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let idx = self.index(x, y);
+                // Negative filter lobes can cancel or outweigh the positive ones, leaving nothing to normalize by.
+                if self.weights[idx] <= Float::EPSILON {
+                    return Color::default();
+                }
+                let mut color = self.sums[idx] / self.weights[idx];
+                color.iter_mut().for_each(|c| *c = c.max(0.0));
+                color.into()
+            })
+            .collect()
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.width + x) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::FilterKind;
+
+    #[test]
+    fn box_filter_averages_pixel_samples() {
+        let mut film = Film::new(2, 1, FilterKind::Box.build(0.5));
+        film.add_sample(0.25, 0.5, Color::new(1.0, 0.0, 0.0));
+        film.add_sample(0.75, 0.5, Color::new(0.0, 1.0, 0.0));
+        film.add_sample(1.5, 0.5, Color::new(0.0, 0.0, 1.0));
+
+        let pixels = film.resolve();
+        assert_eq!(0.5, pixels[0].r());
+        assert_eq!(0.5, pixels[0].g());
+        assert_eq!(1.0, pixels[1].b());
+    }
+
+    #[test]
+    fn wide_filter_spreads_to_neighbors() {
+        let mut film = Film::new(3, 1, FilterKind::Tent.build(1.5));
+        film.add_sample(1.5, 0.5, Color::new(1.0, 1.0, 1.0));
+
+        let pixels = film.resolve();
+        assert!(pixels.iter().all(|p| p.r() == 1.0));
+    }
+
+    #[test]
+    fn rows_are_resolved_top_to_bottom() {
+        let mut film = Film::new(1, 2, FilterKind::Box.build(0.5));
+        film.add_sample(0.5, 1.5, Color::new(1.0, 0.0, 0.0));
+
+        let pixels = film.resolve();
+        assert_eq!(1.0, pixels[0].r());
+        assert_eq!(0.0, pixels[1].r());
+    }
+
+    #[test]
+    fn negative_lobes_stay_within_sample_range() {
+        let color = Color::new(0.8, 0.5, 0.2);
+        let mut film = Film::new(7, 7, FilterKind::Lanczos.build(3.0));
+        film.add_sample(3.5, 3.5, color);
+
+        for pixel in film.resolve() {
+            assert!(pixel.r().is_finite() && pixel.g().is_finite() && pixel.b().is_finite());
+            assert!((0.0..=color.r()).contains(&pixel.r()));
+            assert!((0.0..=color.g()).contains(&pixel.g()));
+            assert!((0.0..=color.b()).contains(&pixel.b()));
+        }
+    }
+
+    #[test]
+    fn negative_total_weight_is_empty() {
+        // The bright sample lands in a negative lobe and outweighs the dark sample's small positive weight.
+        let mut film = Film::new(1, 1, FilterKind::Lanczos.build(3.0));
+        film.add_sample(2.0, 0.5, Color::new(1.0, 1.0, 1.0));
+        film.add_sample(3.1, 0.5, Color::new(0.0, 0.0, 0.0));
+
+        assert_eq!(0.0, film.resolve()[0].r());
+    }
+}
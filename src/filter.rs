@@ -0,0 +1,206 @@
+use crate::float::Float;
+use crate::float::consts::PI;
+use std::str::FromStr;
+
+pub(crate) trait Filter {
+    fn radius(&self) -> Float;
+    fn evaluate(&self, x: Float, y: Float) -> Float;
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum FilterKind {
+    #[default]
+    Box,
+    Tent,
+    Gaussian,
+    Mitchell,
+    Lanczos,
+}
+
+impl FromStr for FilterKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "box" => Ok(FilterKind::Box),
+            "tent" => Ok(FilterKind::Tent),
+            "gaussian" => Ok(FilterKind::Gaussian),
+            "mitchell" => Ok(FilterKind::Mitchell),
+            "lanczos" => Ok(FilterKind::Lanczos),
+            _ => Err(format!("unknown filter `{}`, expected one of: box, tent, gaussian, mitchell, lanczos", s)),
+        }
+    }
+}
+
+impl FilterKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            FilterKind::Box => "box",
+            FilterKind::Tent => "tent",
+            FilterKind::Gaussian => "gaussian",
+            FilterKind::Mitchell => "mitchell",
+            FilterKind::Lanczos => "lanczos",
+        }
+    }
+
+    pub fn default_radius(self) -> Float {
+        match self {
+            FilterKind::Box => 0.5,
+            FilterKind::Tent => 1.0,
+            FilterKind::Gaussian => 1.5,
+            FilterKind::Mitchell => 2.0,
+            FilterKind::Lanczos => 3.0,
+        }
+    }
+
+    pub fn build(self, radius: Float) -> Box<dyn Filter> {
+        match self {
+            FilterKind::Box => Box::new(BoxFilter { radius }),
+            FilterKind::Tent => Box::new(TentFilter { radius }),
+            FilterKind::Gaussian => Box::new(GaussianFilter::new(radius, 2.0)),
+            FilterKind::Mitchell => Box::new(MitchellFilter { radius, b: 1.0 / 3.0, c: 1.0 / 3.0 }),
+            FilterKind::Lanczos => Box::new(LanczosFilter { radius, tau: radius }),
+        }
+    }
+}
+
+pub(crate) struct BoxFilter {
+    radius: Float,
+}
+
+impl Filter for BoxFilter {
+    fn radius(&self) -> Float {
+        self.radius
+    }
+
+    fn evaluate(&self, x: Float, y: Float) -> Float {
+        if x.abs() <= self.radius && y.abs() <= self.radius { 1.0 } else { 0.0 }
+    }
+}
+
+pub(crate) struct TentFilter {
+    radius: Float,
+}
+
+impl Filter for TentFilter {
+    fn radius(&self) -> Float {
+        self.radius
+    }
+
+    fn evaluate(&self, x: Float, y: Float) -> Float {
+        Float::max(0.0, self.radius - x.abs()) * Float::max(0.0, self.radius - y.abs())
+    }
+}
+
+pub(crate) struct GaussianFilter {
+    radius: Float,
+    alpha: Float,
+    edge: Float,
+}
+
+impl GaussianFilter {
+    pub fn new(radius: Float, alpha: Float) -> Self {
+        GaussianFilter { radius, alpha, edge: (-alpha * radius * radius).exp() }
+    }
+
+    fn gaussian(&self, d: Float) -> Float {
+        Float::max(0.0, (-self.alpha * d * d).exp() - self.edge)
+    }
+}
+
+impl Filter for GaussianFilter {
+    fn radius(&self) -> Float {
+        self.radius
+    }
+
+    fn evaluate(&self, x: Float, y: Float) -> Float {
+        self.gaussian(x) * self.gaussian(y)
+    }
+}
+
+pub(crate) struct MitchellFilter {
+    radius: Float,
+    b: Float,
+    c: Float,
+}
+
+impl MitchellFilter {
+    fn mitchell(&self, x: Float) -> Float {
+        let (b, c) = (self.b, self.c);
+        let x = (2.0 * x / self.radius).abs();
+        if x > 2.0 {
+            0.0
+        } else if x > 1.0 {
+            ((-b - 6.0 * c) * x * x * x + (6.0 * b + 30.0 * c) * x * x
+                + (-12.0 * b - 48.0 * c) * x + (8.0 * b + 24.0 * c)) / 6.0
+        } else {
+            ((12.0 - 9.0 * b - 6.0 * c) * x * x * x + (-18.0 + 12.0 * b + 6.0 * c) * x * x
+                + (6.0 - 2.0 * b)) / 6.0
+        }
+    }
+}
+
+impl Filter for MitchellFilter {
+    fn radius(&self) -> Float {
+        self.radius
+    }
+
+    fn evaluate(&self, x: Float, y: Float) -> Float {
+        self.mitchell(x) * self.mitchell(y)
+    }
+}
+
+pub(crate) struct LanczosFilter {
+    radius: Float,
+    tau: Float,
+}
+
+impl LanczosFilter {
+    fn windowed_sinc(&self, x: Float) -> Float {
+        if x.abs() > self.radius {
+            0.0
+        } else {
+            sinc(x) * sinc(x / self.tau)
+        }
+    }
+}
+
+impl Filter for LanczosFilter {
+    fn radius(&self) -> Float {
+        self.radius
+    }
+
+    fn evaluate(&self, x: Float, y: Float) -> Float {
+        self.windowed_sinc(x) * self.windowed_sinc(y)
+    }
+}
+
+fn sinc(x: Float) -> Float {
+    if x.abs() < 1e-5 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_vanish_outside_radius() {
+        for kind in &[FilterKind::Box, FilterKind::Tent, FilterKind::Gaussian, FilterKind::Mitchell, FilterKind::Lanczos] {
+            let filter = kind.build(kind.default_radius());
+            let r = filter.radius();
+            assert!(filter.evaluate(0.0, 0.0) > 0.0);
+            assert_eq!(0.0, filter.evaluate(r + 0.01, 0.0));
+            assert_eq!(0.0, filter.evaluate(0.0, -r - 0.01));
+        }
+    }
+
+    #[test]
+    fn mitchell_has_negative_lobes() {
+        let filter = FilterKind::Mitchell.build(2.0);
+        assert!(filter.evaluate(1.5, 0.0) < 0.0);
+    }
+}
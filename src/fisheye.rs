@@ -0,0 +1,109 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::angle::{Degrees, Radians};
+use crate::camera::{Camera, orthonormal_basis};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum FisheyeMapping {
+    Equidistant,
+    Equisolid,
+}
+
+pub(crate) struct FisheyeCamera {
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    half_fov: Float,
+    aspect_ratio: Float,
+    mapping: FisheyeMapping,
+}
+
+impl FisheyeCamera {
+    pub fn new(
+        origin: Point3,
+        destination: Point3,
+        view_up: Vec3,
+        fov: Degrees,
+        aspect_ratio: Float,
+        mapping: FisheyeMapping,
+    ) -> Self {
+        let (u, v, w) = orthonormal_basis(origin, destination, view_up);
+        FisheyeCamera {
+            origin,
+            u,
+            v,
+            w,
+            half_fov: Float::from(Radians::from(fov)) / 2.0,
+            aspect_ratio,
+            mapping,
+        }
+    }
+}
+
+impl Camera for FisheyeCamera {
+    fn get_ray(&self, u: Float, v: Float, _lens_sample: (Float, Float)) -> Option<Ray> {
+        let (mut x, mut y) = (2.0 * u - 1.0, 2.0 * v - 1.0);
+        if self.aspect_ratio >= 1.0 {
+            x *= self.aspect_ratio;
+        } else {
+            y /= self.aspect_ratio;
+        }
+
+        let r = (x * x + y * y).sqrt();
+        if r > 1.0 {
+            return None;
+        }
+
+        let theta = match self.mapping {
+            FisheyeMapping::Equidistant => r * self.half_fov,
+            FisheyeMapping::Equisolid => 2.0 * (r * (self.half_fov / 2.0).sin()).asin(),
+        };
+        let phi = y.atan2(x);
+
+        Some(Ray::new(
+            self.origin,
+            theta.sin() * (phi.cos() * self.u + phi.sin() * self.v) - theta.cos() * self.w,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera(mapping: FisheyeMapping) -> FisheyeCamera {
+        FisheyeCamera::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Degrees(180.0),
+            2.0,
+            mapping,
+        )
+    }
+
+    #[test]
+    fn center_looks_forward() {
+        for mapping in &[FisheyeMapping::Equidistant, FisheyeMapping::Equisolid] {
+            let ray = camera(*mapping).get_ray(0.5, 0.5, (0.5, 0.5)).unwrap();
+            assert!((ray.direction.z() + 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn image_circle_edge_reaches_half_fov() {
+        for mapping in &[FisheyeMapping::Equidistant, FisheyeMapping::Equisolid] {
+            let ray = camera(*mapping).get_ray(0.5, 1.0, (0.5, 0.5)).unwrap();
+            assert!(ray.direction.z().abs() < 1e-6);
+            assert!((ray.direction.y() - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn outside_image_circle_has_no_ray() {
+        assert!(camera(FisheyeMapping::Equidistant).get_ray(0.0, 0.0, (0.5, 0.5)).is_none());
+    }
+}
@@ -0,0 +1,12 @@
+//! The scalar type of all geometry and color math. The `f64` feature makes it double precision, for scenes
+//! spanning kilometers where `f32` loses the detail of nearby surfaces.
+
+#[cfg(not(feature = "f64"))]
+pub(crate) type Float = f32;
+#[cfg(feature = "f64")]
+pub(crate) type Float = f64;
+
+#[cfg(not(feature = "f64"))]
+pub(crate) use std::f32::consts;
+#[cfg(feature = "f64")]
+pub(crate) use std::f64::consts;
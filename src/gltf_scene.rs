@@ -0,0 +1,248 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::point3::Point3;
+use crate::vec3::Vec3;
+use crate::angle::{Degrees, Radians};
+use crate::animation::Keyframe;
+use crate::hit::Hit;
+use crate::hittable_list::HittableList;
+use crate::scene::LoadedScene;
+use crate::transform::Transform;
+use crate::material_library::MaterialLibrary;
+use crate::material::{Dielectric, Lambertian, Material, Metal};
+use crate::mesh::{Mesh, MeshData};
+use crate::texture::ImageTexture;
+use crate::tone_mapping::srgb_decode;
+use gltf::image::Format;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+struct Importer {
+    buffers: Vec<gltf::buffer::Data>,
+    images: Vec<gltf::image::Data>,
+    materials: HashMap<Option<usize>, Rc<dyn Material>>,
+    world: HittableList,
+    camera: Option<(Transform, Degrees)>,
+}
+
+/// Reads a `.gltf` or `.glb` file with its buffers and images. The view comes from the first perspective
+/// camera, dropping its roll since our views are always upright.
+pub(crate) fn import(path: &str) -> Result<LoadedScene, String> {
+    let (document, buffers, images) = gltf::import(path).map_err(|e| format!("can't import `{}`: {}", path, e))?;
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| format!("`{}` contains no scene", path))?;
+
+    let mut importer = Importer { buffers, images, materials: HashMap::new(), world: HittableList::default(), camera: None };
+    for node in scene.nodes() {
+        importer.visit(&node, &Transform::identity()).map_err(|e| format!("`{}`: {}", path, e))?;
+    }
+
+    let view = importer.camera.map(|(transform, fov)| {
+        let origin = transform.point(Point3::new(0.0, 0.0, 0.0));
+        let forward = transform.vector(Vec3::new(0.0, 0.0, -1.0)).unit_vec();
+        let focus_dist = importer
+            .world
+            .bounding_box()
+            .map(|b| Vec3::from(b.min + 0.5 * (Vec3::from(b.max) - Vec3::from(b.min))) - Vec3::from(origin))
+            .map_or(10.0, |to_center| to_center.dot(&forward).max(0.1));
+        Keyframe { time: 0.0, origin, destination: origin + forward, fov, focus_dist }
+    });
+    Ok(LoadedScene { world: importer.world, view, materials: MaterialLibrary::new(), options: Vec::new() })
+}
+
+impl Importer {
+    fn visit(&mut self, node: &gltf::Node, parent: &Transform) -> Result<(), String> {
+        let transform = Transform::from_columns(node.transform().matrix().map(|column| column.map(|v| v as Float))).then(parent);
+
+        if let (None, Some(camera)) = (self.camera, node.camera()) {
+            if let gltf::camera::Projection::Perspective(perspective) = camera.projection() {
+                self.camera = Some((transform, Degrees::from(Radians(perspective.yfov() as Float))));
+            }
+        }
+
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                if primitive.mode() != gltf::mesh::Mode::Triangles {
+                    eprintln!("Skipping {:?} primitive of mesh {}", primitive.mode(), mesh.index());
+                    continue;
+                }
+                let mesh = self.mesh(&primitive, &transform)?;
+                self.world.add(Rc::new(mesh));
+            }
+        }
+
+        for child in node.children() {
+            self.visit(&child, &transform)?;
+        }
+        Ok(())
+    }
+
+    fn mesh(&mut self, primitive: &gltf::Primitive, transform: &Transform) -> Result<Mesh, String> {
+        let buffers = &self.buffers;
+        let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data[..]));
+
+        let positions: Vec<Point3> = reader
+            .read_positions()
+            .ok_or_else(|| "mesh primitive has no positions".to_string())?
+            .map(|[x, y, z]| transform.point(Point3::new(x as Float, y as Float, z as Float)))
+            .collect();
+        let normals: Vec<Vec3> = reader
+            .read_normals()
+            .map(|normals| normals.map(|[x, y, z]| transform.normal(Vec3::new(x as Float, y as Float, z as Float))).collect())
+            .unwrap_or_default();
+
+        let material = primitive.material();
+        let tex_coord = material.pbr_metallic_roughness().base_color_texture().map_or(0, |info| info.tex_coord());
+        let uvs: Vec<(Float, Float)> = reader
+            .read_tex_coords(tex_coord)
+            .map(|uvs| uvs.into_f32().map(|[u, v]| (u as Float, 1.0 - v as Float)).collect())
+            .unwrap_or_default();
+
+        let indices: Vec<usize> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().map(|i| i as usize).collect(),
+            None => (0..positions.len()).collect(),
+        };
+        let mirrored = transform.determinant() < 0.0;
+        let triangles = indices
+            .chunks_exact(3)
+            .map(|t| if mirrored { [t[0], t[2], t[1]] } else { [t[0], t[1], t[2]] })
+            .collect();
+
+        let colors = reader
+            .read_colors(0)
+            .map(|colors| colors.into_rgb_f32().map(|[r, g, b]| Color::new(r as Float, g as Float, b as Float)).collect())
+            .unwrap_or_default();
+
+        let material = self.material(&material)?;
+        Mesh::new(MeshData { positions, normals, uvs, colors, triangles }, material)
+    }
+
+    /// Maps metallic-roughness parameters onto the closest of our materials, sharing one instance per glTF material.
+    fn material(&mut self, material: &gltf::Material) -> Result<Rc<dyn Material>, String> {
+        if let Some(shared) = self.materials.get(&material.index()) {
+            return Ok(shared.clone());
+        }
+
+        let pbr = material.pbr_metallic_roughness();
+        let [r, g, b, _] = pbr.base_color_factor();
+        let base_color = Color::new(r as Float, g as Float, b as Float);
+        let transmission = material.transmission().map_or(0.0, |t| t.transmission_factor());
+
+        let result: Rc<dyn Material> = if transmission > 0.5 {
+            Rc::new(Dielectric::new(material.ior().unwrap_or(1.5) as Float))
+        } else if pbr.metallic_factor() >= 0.5 {
+            Rc::new(Metal::new(base_color, pbr.roughness_factor() as Float))
+        } else if let Some(info) = pbr.base_color_texture() {
+            let image = self
+                .images
+                .get(info.texture().source().index())
+                .ok_or_else(|| format!("missing image for texture {}", info.texture().index()))?;
+            Rc::new(Lambertian::textured(Rc::new(texture(image, base_color)?)))
+        } else {
+            Rc::new(Lambertian::new(base_color))
+        };
+
+        self.materials.insert(material.index(), result.clone());
+        Ok(result)
+    }
+}
+
+/// Decodes an sRGB base color image into linear colors tinted by `factor`.
+fn texture(image: &gltf::image::Data, factor: Color) -> Result<ImageTexture, String> {
+    let (channels, bytes) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        format => return Err(format!("unsupported base color image format {:?}", format)),
+    };
+
+    let sample = |pixel: &[u8], channel: usize| {
+        let value = match bytes {
+            1 => pixel[channel] as Float / 255.0,
+            _ => u16::from_le_bytes([pixel[2 * channel], pixel[2 * channel + 1]]) as Float / 65535.0,
+        };
+        srgb_decode(value)
+    };
+    let pixels = image
+        .pixels
+        .chunks_exact(channels * bytes)
+        .map(|pixel| match channels {
+            1 | 2 => {
+                let gray = sample(pixel, 0);
+                Color::new(gray * factor.r(), gray * factor.g(), gray * factor.b())
+            },
+            _ => Color::new(sample(pixel, 0) * factor.r(), sample(pixel, 1) * factor.g(), sample(pixel, 2) * factor.b()),
+        })
+        .collect();
+    Ok(ImageTexture::new(image.width as usize, image.height as usize, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const TRIANGLE_GLTF: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0, 1] }],
+        "nodes": [
+            { "mesh": 0, "translation": [0.0, 0.0, -2.0], "children": [2] },
+            { "camera": 0, "translation": [0.0, 1.0, 5.0] },
+            { "mesh": 0, "scale": [-1.0, 1.0, 1.0] }
+        ],
+        "cameras": [{ "type": "perspective", "perspective": { "yfov": 0.5, "znear": 0.1 } }],
+        "materials": [{ "pbrMetallicRoughness": { "baseColorFactor": [0.9, 0.6, 0.2, 1.0], "metallicFactor": 1.0, "roughnessFactor": 0.25 } }],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "material": 0 }] }],
+        "accessors": [{
+            "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+            "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0]
+        }],
+        "bufferViews": [{ "buffer": 0, "byteLength": 36 }],
+        "buffers": [{ "byteLength": 36, "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAA" }]
+    }"#;
+
+    #[test]
+    fn imports_nodes_materials_and_camera() {
+        let path = std::env::temp_dir().join("raytracing-import-test.gltf");
+        fs::write(&path, TRIANGLE_GLTF).unwrap();
+        let scene = import(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let bbox = scene.world.bounding_box().unwrap();
+        assert!((Vec3::from(bbox.min) - Vec3::new(-1.0, 0.0, -2.0)).length() < 1e-3);
+        assert!((Vec3::from(bbox.max) - Vec3::new(1.0, 1.0, -2.0)).length() < 1e-3);
+
+        let ray = crate::ray::Ray::new(Point3::new(-0.25, 0.25, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = scene.world.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((rec.t - 2.0).abs() < 1e-6);
+        let albedo = rec.material.albedo(&rec);
+        assert!((albedo.g() - 0.6).abs() < 1e-6);
+
+        let view = scene.view.unwrap();
+        assert_eq!(Point3::new(0.0, 1.0, 5.0), view.origin);
+        assert_eq!(Point3::new(0.0, 1.0, 4.0), view.destination);
+        assert!((view.fov.0 - Float::to_degrees(0.5)).abs() < 1e-4);
+        assert!((view.focus_dist - 7.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn normals_follow_non_uniform_scale() {
+        let n = Transform::scale(Vec3::new(2.0, 1.0, 1.0)).normal(Vec3::new(1.0, 1.0, 0.0));
+        assert!((n.x() - 1.0 / Float::sqrt(5.0)).abs() < 1e-6);
+        assert!((n.y() - 2.0 / Float::sqrt(5.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn missing_file_is_reported() {
+        let error = import("does-not-exist.gltf").err().unwrap();
+        assert!(error.starts_with("can't import `does-not-exist.gltf`"));
+    }
+}
@@ -0,0 +1,226 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::hit::{Hit, HitRecord};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::triangle::intersect_triangle;
+use crate::pgm;
+use std::rc::Rc;
+
+/// Grid indices of a sample along x and z.
+type Sample = (usize, usize);
+
+/// Terrain sampled on a regular grid, each cell split into two triangles with smoothly interpolated normals.
+pub(crate) struct Heightfield {
+    corner: Point3,
+    size: Vec3,
+    columns: usize,
+    rows: usize,
+    heights: Vec<Float>,
+    normals: Vec<Vec3>,
+    bounds: Aabb,
+    material: Rc<dyn Material>,
+}
+
+impl Heightfield {
+    /// `heights` holds `columns * rows` samples in `0.0..=1.0`, row by row along z. The terrain covers
+    /// `size.x() * size.z()` starting at `corner`, with a sample of 1 reaching `size.y()` above it.
+    pub fn new(columns: usize, rows: usize, heights: Vec<Float>, corner: Point3, size: Vec3, material: Rc<dyn Material>) -> Result<Self, String> {
+        if columns < 2 || rows < 2 || heights.len() != columns * rows {
+            return Err(format!("heightfield needs at least 2x2 samples, got {} for {}x{}", heights.len(), columns, rows));
+        }
+
+        let heights: Vec<Float> = heights.into_iter().map(|h| corner.y() + h * size.y()).collect();
+        let (low, high) = heights.iter().fold((Float::INFINITY, Float::NEG_INFINITY), |(low, high), h| (low.min(*h), high.max(*h)));
+        let bounds = Aabb::new(
+            Point3::new(corner.x(), low, corner.z()),
+            Point3::new(corner.x() + size.x(), high, corner.z() + size.z()),
+        );
+
+        let mut field = Heightfield { corner, size, columns, rows, heights, normals: Vec::new(), bounds, material };
+        field.normals = (0..rows)
+            .flat_map(|j| (0..columns).map(move |i| (i, j)))
+            .map(|(i, j)| field.vertex_normal(i, j))
+            .collect();
+        Ok(field)
+    }
+
+    pub fn from_fn(columns: usize, rows: usize, height: impl Fn(Float, Float) -> Float, corner: Point3, size: Vec3, material: Rc<dyn Material>) -> Self {
+        let heights = (0..rows)
+            .flat_map(|j| (0..columns).map(move |i| (i, j)))
+            .map(|(i, j)| height(i as Float / (columns - 1) as Float, j as Float / (rows - 1) as Float))
+            .collect();
+        Heightfield::new(columns, rows, heights, corner, size, material).expect("heightfield sample count")
+    }
+
+    /// Loads a grayscale PGM image, mapping image rows to z and columns to x.
+    pub fn from_pgm(path: &str, corner: Point3, size: Vec3, material: Rc<dyn Material>) -> Result<Self, String> {
+        let image = pgm::read(path)?;
+        Heightfield::new(image.width, image.height, image.values, corner, size, material)
+            .map_err(|e| format!("`{}`: {}", path, e))
+    }
+
+    fn cell_size(&self) -> (Float, Float) {
+        (self.size.x() / (self.columns - 1) as Float, self.size.z() / (self.rows - 1) as Float)
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Point3 {
+        let (dx, dz) = self.cell_size();
+        Point3::new(
+            self.corner.x() + i as Float * dx,
+            self.heights[j * self.columns + i],
+            self.corner.z() + j as Float * dz,
+        )
+    }
+
+    fn vertex_normal(&self, i: usize, j: usize) -> Vec3 {
+        let (dx, dz) = self.cell_size();
+        let height = |i: usize, j: usize| self.heights[j * self.columns + i];
+        let (left, right) = (i.saturating_sub(1), (i + 1).min(self.columns - 1));
+        let (back, front) = (j.saturating_sub(1), (j + 1).min(self.rows - 1));
+        let slope_x = (height(right, j) - height(left, j)) / ((right - left) as Float * dx);
+        let slope_z = (height(i, front) - height(i, back)) / ((front - back) as Float * dz);
+        Vec3::new(-slope_x, 1.0, -slope_z).unit_vec()
+    }
+
+    fn hit_cell(&self, ray: &Ray, i: usize, j: usize, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let mut closest: Option<(Float, [Sample; 3], Float, Float)> = None;
+        for triangle in [[corners[0], corners[1], corners[2]], [corners[0], corners[2], corners[3]]] {
+            let t_max = closest.map_or(t_max, |(t, ..)| t);
+            let vertices = [0, 1, 2].map(|k| self.vertex(triangle[k].0, triangle[k].1));
+            if let Some((t, b1, b2)) = intersect_triangle(ray, vertices, t_min, t_max) {
+                closest = Some((t, triangle, b1, b2));
+            }
+        }
+
+        let (t, triangle, b1, b2) = closest?;
+        let normal = |(i, j): Sample| self.normals[j * self.columns + i];
+        let outward_normal = ((1.0 - b1 - b2) * normal(triangle[0]) + b1 * normal(triangle[1]) + b2 * normal(triangle[2])).unit_vec();
+
+        let p = ray.at(t);
+        let u = (p.x() - self.corner.x()) / self.size.x();
+        let v = (p.z() - self.corner.z()) / self.size.z();
+        let mut rec = HitRecord::new(p, outward_normal, t, (u.clamp(0.0, 1.0), v.clamp(0.0, 1.0)), self.material.clone());
+        rec.set_face_normal(ray, &outward_normal);
+        Some(rec)
+    }
+}
+
+impl Hit for Heightfield {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let (t_enter, t_exit) = self.bounds.clip(ray, t_min, t_max)?;
+
+        // Walk the cells under the ray in order (Amanatides–Woo), so the first hit is the closest.
+        let (dx, dz) = self.cell_size();
+        let (cells_x, cells_z) = (self.columns - 1, self.rows - 1);
+        let entry = ray.at(t_enter);
+        let cell = |offset: Float, size: Float, cells: usize| ((offset / size).floor().max(0.0) as usize).min(cells - 1);
+        let mut i = cell(entry.x() - self.corner.x(), dx, cells_x);
+        let mut j = cell(entry.z() - self.corner.z(), dz, cells_z);
+
+        let axis = |index: usize, origin: Float, direction: Float, corner: Float, size: Float| {
+            if direction == 0.0 {
+                return (Float::INFINITY, Float::INFINITY);
+            }
+            let boundary = corner + (index + (direction > 0.0) as usize) as Float * size;
+            ((boundary - origin) / direction, size / direction.abs())
+        };
+        let (mut next_x, delta_x) = axis(i, ray.origin.x(), ray.direction.x(), self.corner.x(), dx);
+        let (mut next_z, delta_z) = axis(j, ray.origin.z(), ray.direction.z(), self.corner.z(), dz);
+
+        loop {
+            if let Some(rec) = self.hit_cell(ray, i, j, t_min, t_max) {
+                return Some(rec);
+            }
+
+            if next_x < next_z {
+                if next_x > t_exit {
+                    return None;
+                }
+                next_x += delta_x;
+                match if ray.direction.x() > 0.0 { i.checked_add(1).filter(|i| *i < cells_x) } else { i.checked_sub(1) } {
+                    Some(next) => i = next,
+                    None => return None,
+                }
+            } else {
+                if next_z > t_exit {
+                    return None;
+                }
+                next_z += delta_z;
+                match if ray.direction.z() > 0.0 { j.checked_add(1).filter(|j| *j < cells_z) } else { j.checked_sub(1) } {
+                    Some(next) => j = next,
+                    None => return None,
+                }
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+
+    fn field(height: impl Fn(Float, Float) -> Float) -> Heightfield {
+        let material = Rc::new(Lambertian::new(Color::default()));
+        Heightfield::from_fn(11, 11, height, Point3::new(-5.0, 0.0, -5.0), Vec3::new(10.0, 2.0, 10.0), material)
+    }
+
+    #[test]
+    fn flat_field_hit_from_above() {
+        let flat = field(|_, _| 0.5);
+        let ray = Ray::new(Point3::new(1.3, 5.0, -2.7), Vec3::new(0.0, -1.0, 0.0));
+        let rec = flat.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((rec.t - 4.0).abs() < 1e-5);
+        assert!((rec.normal.y() - 1.0).abs() < 1e-5);
+        assert!((rec.u - 0.63).abs() < 1e-5);
+        assert!((rec.v - 0.23).abs() < 1e-5);
+    }
+
+    #[test]
+    fn grazing_ray_hits_the_first_ridge() {
+        // A ridge along z at x = 0 blocks a ray travelling along +x just above the ground.
+        let ridge = field(|x, _| if (x - 0.5).abs() < 0.05 { 1.0 } else { 0.0 });
+        let ray = Ray::new(Point3::new(-10.0, 1.0, 0.25), Vec3::new(1.0, 0.0, 0.0));
+        let rec = ridge.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((rec.p.x() + 0.5).abs() < 1e-4);
+        assert!(rec.normal.x() < 0.0);
+
+        let over = Ray::new(Point3::new(-10.0, 2.5, 0.25), Vec3::new(1.0, 0.0, 0.0));
+        assert!(ridge.hit(&over, 0.001, Float::INFINITY).is_none());
+    }
+
+    #[test]
+    fn matches_brute_force_over_cells() {
+        let hills = field(|x, z| 0.5 + 0.5 * (6.0 * x).sin() * (5.0 * z).cos());
+        for k in 0..50 {
+            let a = k as Float * 0.37;
+            let ray = Ray::new(Point3::new(7.0 * a.cos(), 3.0, 7.0 * a.sin()), Vec3::new(-a.cos(), -0.3, -a.sin() + 0.2));
+
+            let mut expected: Option<Float> = None;
+            for j in 0..10 {
+                for i in 0..10 {
+                    if let Some(rec) = hills.hit_cell(&ray, i, j, 0.001, expected.unwrap_or(Float::INFINITY)) {
+                        expected = Some(rec.t);
+                    }
+                }
+            }
+            assert_eq!(expected, hills.hit(&ray, 0.001, Float::INFINITY).map(|rec| rec.t));
+        }
+    }
+
+    #[test]
+    fn sample_count_is_validated() {
+        let material = Rc::new(Lambertian::new(Color::default()));
+        let result = Heightfield::new(2, 2, vec![0.0; 3], Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0), material);
+        assert!(result.is_err());
+    }
+}
@@ -1,20 +1,69 @@
+use crate::float::Float;
 use crate::vec3::Vec3;
+use crate::color::Color;
 use crate::point3::Point3;
 use crate::ray::Ray;
 use std::rc::Rc;
 use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::scene_file::SceneWriter;
+
+/// Rounding error allowed for a computed hit point, in units of the precision of its largest coordinate.
+const POINT_ERROR_ULPS: Float = 32.0;
+
+/// Largest absolute coordinate of `p`, the scale its rounding error grows with.
+pub(crate) fn magnitude(p: Point3) -> Float {
+    p.iter().fold(0.0, |max, c| max.max(c.abs()))
+}
+
+/// Bound on the rounding error of a point computed from values no larger than `magnitude`.
+pub(crate) fn rounding_error(magnitude: Float) -> Float {
+    POINT_ERROR_ULPS * Float::EPSILON * magnitude
+}
 
 pub(crate) struct HitRecord {
     pub p: Point3,
     pub normal: Vec3,
-    pub t: f32,
+    pub t: Float,
+    pub u: Float,
+    pub v: Float,
+    /// How far `p` may be from the true surface through rounding. Rays leaving the surface start this far
+    /// off it along the normal.
+    pub error: Float,
     pub front_face: bool,
+    pub object_id: u32,
+    /// Interpolated per-vertex color of meshes that carry one, tinting the material's albedo.
+    pub vertex_color: Option<Color>,
     pub material: Rc<dyn Material>
 }
 
 impl HitRecord {
-    pub fn new(p: Point3, normal: Vec3, t: f32, material: Rc<dyn Material>) -> Self {
-        HitRecord { p, normal, t, front_face: (true), material }
+    pub fn new(p: Point3, normal: Vec3, t: Float, (u, v): (Float, Float), material: Rc<dyn Material>) -> Self {
+        let error = rounding_error(magnitude(p));
+        HitRecord { p, normal, t, u, v, error, front_face: (true), object_id: 0, vertex_color: None, material }
+    }
+
+    /// Widens the error bound by the rounding of points computed along `ray`, which every primitive that
+    /// solves for `t` inherits.
+    pub fn include_ray_error(&mut self, ray: &Ray) {
+        let scale = magnitude(ray.origin) + self.t.abs() * ray.direction.length();
+        self.error = self.error.max(rounding_error(scale));
+    }
+
+    /// Ray leaving the surface in `direction`. It starts off the surface on the side it heads to, by the
+    /// error bound of `p` and then rounded away, so it can't hit the surface it leaves again.
+    pub fn spawn_ray(&self, direction: Vec3) -> Ray {
+        let distance = if Vec3::dot(&direction, &self.normal) < 0.0 { -self.error } else { self.error };
+        let offset = distance * self.normal;
+        let mut origin = self.p + offset;
+        for (c, o) in origin.iter_mut().zip(offset.iter()) {
+            if *o > 0.0 {
+                *c = c.next_up();
+            } else if *o < 0.0 {
+                *c = c.next_down();
+            }
+        }
+        Ray::new(origin, direction)
     }
 }
 
@@ -26,5 +75,40 @@ impl HitRecord {
 }
 
 pub(crate) trait Hit {
-    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord>;
-}
\ No newline at end of file
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord>;
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    /// Adds the object's scene file statements, or returns `None` if the format can't describe it.
+    fn export(&self, _scene: &mut SceneWriter) -> Option<()> {
+        None
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sphere::Sphere;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn spawned_rays_leave_large_distant_spheres() {
+        let material = Rc::new(Lambertian::new(Color::default()));
+        for center in [Point3::new(0.0, -1000.0, 0.0), Point3::new(5000.0, -1000.0, 3000.0)] {
+            let ground = Sphere::new(center, 1000.0, material.clone());
+            let top = Vec3::from(center) + Vec3::new(0.0, 1000.0, 0.0);
+            for k in 0..500 {
+                let a = k as Float * 0.37;
+                let origin = Point3::from(top + Vec3::new(13.0 * a.cos(), 2.0 + (k % 5) as Float, 13.0 * a.sin()));
+                let target = top + Vec3::new(0.3 * (k % 7) as Float, 0.0, -0.2 * (k % 11) as Float);
+                let ray = Ray::new(origin, target - Vec3::from(origin));
+                let mut rec = ground.hit(&ray, 0.0, Float::INFINITY).unwrap();
+                rec.include_ray_error(&ray);
+
+                // Grazing and steep directions both have to clear the surface they start on.
+                let bounce = rec.spawn_ray(Vec3::new(a.sin(), 0.01 + 0.5 * (k % 3) as Float, a.cos()));
+                assert!(ground.hit(&bounce, 0.0, Float::INFINITY).is_none(), "ray {} hit its own surface", k);
+                let inside = rec.spawn_ray(-rec.normal + Vec3::new(0.1 * a.cos(), 0.0, 0.1 * a.sin()));
+                assert!(ground.hit(&inside, 0.0, Float::INFINITY).is_some_and(|h| h.t > 1.0), "ray {} stuck at the surface", k);
+            }
+        }
+    }
+}
@@ -1,6 +1,10 @@
+use crate::float::Float;
 use std::rc::Rc;
 use crate::hit::{Hit, HitRecord};
 use crate::ray::Ray;
+use crate::stats;
+use crate::aabb::Aabb;
+use crate::scene_file::SceneWriter;
 
 #[derive(Default)]
 pub(crate) struct HittableList {
@@ -12,25 +16,69 @@ impl HittableList {
         self.objects.push(object);
     }
 
+    // Only the tests empty a list; the renderer builds each world once.
+    #[cfg_attr(not(test), allow(dead_code))]
     pub fn clear(&mut self) {
         self.objects.clear();
     }
 }
 
 impl Hit for HittableList {
-    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
         let mut closest_so_far = t_max;
+        stats::count_intersection_tests(self.objects.len() as u64);
 
         self.objects
             .iter()
-            .filter_map(|object| {
+            .enumerate()
+            .filter_map(|(id, object)| {
                 object
                     .hit(ray, t_min, closest_so_far)
-                    .and_then(|hit_rec| {
+                    .map(|hit_rec| {
                         closest_so_far = hit_rec.t;
-                        Some(hit_rec)
+                        HitRecord { object_id: id as u32, ..hit_rec }
                     })
             })
             .last()
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut boxes = self.objects.iter().map(|object| object.bounding_box());
+        let first = boxes.next()??;
+        boxes.try_fold(first, |surrounding, bbox| Some(surrounding.surrounding(&bbox?)))
+    }
+
+    fn export(&self, scene: &mut SceneWriter) -> Option<()> {
+        for object in &self.objects {
+            scene.add(object.as_ref());
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+    use crate::point3::Point3;
+    use crate::sphere::Sphere;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn closest_hit_wins_and_clear_empties_the_list() {
+        let material = Rc::new(Lambertian::new(Color::default()));
+        let mut world = HittableList::default();
+        world.add(Rc::new(Sphere::new(Point3::new(0.0, 0.0, -10.0), 1.0, material.clone())));
+        world.add(Rc::new(Sphere::new(Point3::new(0.0, 0.0, -5.0), 1.0, material)));
+
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = world.hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 4.0).abs() < 1e-5);
+        assert_eq!(1, rec.object_id);
+
+        world.clear();
+        assert!(world.hit(&ray, 0.0, Float::INFINITY).is_none());
+        assert!(world.bounding_box().is_none());
+    }
 }
@@ -0,0 +1,140 @@
+use crate::float::Float;
+use crate::hit::{self, Hit, HitRecord};
+use crate::ray::Ray;
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::transform::Transform;
+use std::rc::Rc;
+
+/// An object placed in the world by a transform. Any number of instances can share the object, and with it
+/// the bottom-level BVH of a mesh.
+pub(crate) struct Instance {
+    object: Rc<dyn Hit>,
+    transform: Transform,
+    to_object: Transform,
+    bounds: Option<Aabb>,
+}
+
+impl Instance {
+    pub fn new(object: Rc<dyn Hit>, transform: Transform) -> Self {
+        let bounds = object.bounding_box().map(|bounds| transform.bounding_box(&bounds));
+        Instance { object, transform, to_object: transform.inverse(), bounds }
+    }
+}
+
+impl Hit for Instance {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        // The direction stays unnormalized so distances along the ray agree in both spaces.
+        let local = Ray::new(self.to_object.point(ray.origin), self.to_object.vector(ray.direction));
+        let mut rec = self.object.hit(&local, t_min, t_max)?;
+        rec.p = self.transform.point(rec.p);
+        rec.normal = self.transform.normal(rec.normal);
+        rec.error = rec.error * self.transform.max_stretch() + hit::rounding_error(hit::magnitude(rec.p));
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bounds
+    }
+}
+
+/// Top-level acceleration structure: a BVH over instances, each bringing its own bottom-level structure.
+/// Unbounded instances, like transformed planes, are tested on every ray.
+pub(crate) struct Tlas {
+    instances: Vec<Instance>,
+    bounded: Vec<usize>,
+    unbounded: Vec<usize>,
+    bvh: Bvh,
+}
+
+impl Tlas {
+    pub fn new(instances: Vec<Instance>) -> Self {
+        let (bounded, unbounded): (Vec<usize>, Vec<usize>) = (0..instances.len()).partition(|i| instances[*i].bounds.is_some());
+        let boxes: Vec<Aabb> = bounded.iter().filter_map(|i| instances[*i].bounds).collect();
+        let bvh = Bvh::new(&boxes);
+        Tlas { instances, bounded, unbounded, bvh }
+    }
+}
+
+impl Hit for Tlas {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let mut closest = self.bvh.hit(ray, t_min, t_max, |i, t_max| {
+            self.instances[self.bounded[i]].hit(ray, t_min, t_max).map(|rec| (rec.t, rec))
+        });
+        for i in &self.unbounded {
+            let t_max = closest.as_ref().map_or(t_max, |rec| rec.t);
+            if let Some(rec) = self.instances[*i].hit(ray, t_min, t_max) {
+                closest = Some(rec);
+            }
+        }
+        closest
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if self.unbounded.is_empty() { self.bvh.bounds() } else { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::point3::Point3;
+    use crate::vec3::Vec3;
+    use crate::angle::Degrees;
+    use crate::sphere::Sphere;
+    use crate::plane::Plane;
+    use crate::cuboid::Cuboid;
+    use crate::hittable_list::HittableList;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn transformed_hits_are_in_world_space() {
+        let material = Rc::new(Lambertian::new(Color::default()));
+        let unit: Rc<dyn Hit> = Rc::new(Cuboid::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0), material));
+        let transform = Transform::scale(Vec3::new(2.0, 1.0, 1.0))
+            .then(&Transform::rotate(Vec3::new(0.0, 1.0, 0.0), Degrees(90.0)))
+            .then(&Transform::translate(Vec3::new(0.0, 0.0, -10.0)));
+        let instance = Instance::new(unit, transform);
+
+        // Rotated, the long axis runs along z, so the front face sits at z = -8.
+        let rec = instance.hit(&Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -0.5)), 0.001, Float::INFINITY).unwrap();
+        assert!((rec.t - 16.0).abs() < 1e-4);
+        assert!((rec.p.z() + 8.0).abs() < 1e-4);
+        assert!((rec.normal.z() - 1.0).abs() < 1e-5);
+
+        let bounds = instance.bounding_box().unwrap();
+        assert!((bounds.min.z() + 12.0).abs() < 1e-4 && (bounds.max.x() - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn agrees_with_a_flat_list() {
+        let material = Rc::new(Lambertian::new(Color::default()));
+        let sphere: Rc<dyn Hit> = Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, material.clone()));
+        let mut instances = Vec::new();
+        let mut list = HittableList::default();
+        for k in 0..300 {
+            let a = k as Float * 0.61;
+            let (center, radius) = (Vec3::new(20.0 * a.cos(), 3.0 * (k as Float * 0.13).sin(), 20.0 * a.sin()), 0.3 + 0.2 * a.sin().abs());
+            let transform = Transform::scale(Vec3::new(radius, radius, radius)).then(&Transform::translate(center));
+            instances.push(Instance::new(sphere.clone(), transform));
+            list.add(Rc::new(Sphere::new(Point3::from(center), radius, material.clone())));
+        }
+        let ground = Rc::new(Plane::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), material.clone()));
+        instances.push(Instance::new(ground.clone(), Transform::translate(Vec3::new(0.0, -2.0, 0.0))));
+        list.add(Rc::new(Plane::new(Point3::new(0.0, -2.0, 0.0), Vec3::new(0.0, 1.0, 0.0), material)));
+        let tlas = Tlas::new(instances);
+        assert!(tlas.bounding_box().is_none());
+
+        for k in 0..200 {
+            let a = k as Float * 0.173;
+            let ray = Ray::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(a.cos(), -0.05 * (k % 7) as Float, a.sin()));
+            let expected = list.hit(&ray, 0.001, Float::INFINITY).map(|rec| rec.t);
+            let actual = tlas.hit(&ray, 0.001, Float::INFINITY).map(|rec| rec.t);
+            match (expected, actual) {
+                (Some(e), Some(a)) => assert!((e - a).abs() < 1e-3 * e.max(1.0), "{} vs {}", e, a),
+                (e, a) => assert_eq!(e, a),
+            }
+        }
+    }
+}
@@ -0,0 +1,444 @@
+mod progress_bar;
+mod float;
+mod angle;
+mod vec3;
+mod simd;
+mod color;
+mod point3;
+mod ray;
+mod hit;
+mod sphere;
+mod aabb;
+mod roots;
+mod plane;
+mod disk;
+mod cylinder;
+mod cone;
+mod torus;
+mod cuboid;
+mod csg;
+mod sdf;
+mod transform;
+mod triangle;
+mod heightfield;
+mod bvh;
+mod mesh;
+mod instance;
+mod gltf_scene;
+mod ply;
+mod stl;
+mod hittable_list;
+mod camera;
+mod material;
+mod material_library;
+mod aperture;
+mod pgm;
+mod orthographic;
+mod fisheye;
+mod equirectangular;
+mod perlin;
+mod texture;
+mod scene;
+mod scene_file;
+mod settings;
+mod random;
+mod output;
+mod animation;
+mod sampler;
+mod filter;
+mod film;
+mod tone_mapping;
+mod exr;
+mod aov;
+mod denoise;
+mod stats;
+pub mod bench;
+#[cfg(feature = "preview")]
+mod preview;
+
+use crate::color::Color;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::point3::Point3;
+use crate::hit::Hit;
+use crate::camera::{Camera, PerspectiveCamera, Projection};
+use crate::orthographic::OrthographicCamera;
+use crate::fisheye::FisheyeCamera;
+use crate::equirectangular::EquirectangularCamera;
+use crate::hittable_list::HittableList;
+use crate::aperture::{Aperture, ApertureMask};
+use crate::angle::Radians;
+use crate::progress_bar::ProgressBar;
+use crate::settings::Settings;
+use crate::film::Film;
+use crate::sampler::Sampler;
+use crate::animation::{Keyframe, CameraPath};
+use crate::output::{ImageFormat, ImageSettings};
+use crate::aov::{Aov, AovFilm, AovImage, FirstHit, PathSample};
+use crate::denoise::Denoiser;
+use crate::stats::Stats;
+use crate::tone_mapping::PostProcess;
+use crate::float::Float;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+const ASPECT_RATIO: Float = 16.0 / 9.0;
+const IMAGE_WIDTH: u32 = 1600;
+const IMAGE_HEIGHT: u32 = (IMAGE_WIDTH as Float / ASPECT_RATIO) as u32;
+const SAMPLES_PER_PIXEL: u32 = 100;
+const MAX_DEPTH: u32 = 50;
+
+/// Panoramas cover 360° by 180°, so they're rendered twice as wide as they're tall.
+fn image_size(projection: Projection) -> (u32, u32) {
+    match projection {
+        Projection::Equirectangular => (IMAGE_WIDTH, IMAGE_WIDTH / 2),
+        _ => (IMAGE_WIDTH, IMAGE_HEIGHT),
+    }
+}
+
+/// Maps a point on the film, in pixels, to the camera's unit square, with the image edges at 0 and 1.
+fn film_to_screen((x, y): (Float, Float), (width, height): (u32, u32)) -> (Float, Float) {
+    (x / width as Float, y / height as Float)
+}
+
+fn trace(ray: &Ray, world: &impl Hit, sampler: &mut dyn Sampler) -> PathSample {
+    let mut sample = PathSample::default();
+    let mut throughput = Vec3::new(1.0, 1.0, 1.0);
+    let mut ray = *ray;
+    let mut segments = 0;
+
+    for bounce in 0..MAX_DEPTH {
+        stats::count_ray();
+        segments += 1;
+        // Scattered rays start off their surface, so no epsilon is needed to skip it.
+        match world.hit(&ray, 0.0, Float::INFINITY) {
+            Some(mut hit_rec) => {
+                hit_rec.include_ray_error(&ray);
+                if bounce == 0 {
+                    sample.first_hit = Some(FirstHit {
+                        depth: hit_rec.t * ray.direction.length(),
+                        normal: hit_rec.normal,
+                        albedo: hit_rec.material.albedo(&hit_rec),
+                        object_id: hit_rec.object_id,
+                        material_id: Rc::as_ptr(&hit_rec.material) as *const u8 as usize,
+                    });
+                }
+                match hit_rec.material.scatter(&ray, &hit_rec, sampler) {
+                    Some(r) => {
+                        throughput = throughput * Vec3::from(r.attenuation);
+                        ray = r.scattered;
+                    }
+                    None => break,
+                }
+            }
+            None => {
+                let unit_direction = ray.direction.unit_vec();
+                let t = 0.5 * (unit_direction.y() + 1.0);
+                let sky = (1.0 - t) * Vec3::new(1.0, 1.0, 1.0) + t * Vec3::new(0.5, 0.7, 1.0);
+                if bounce <= 1 {
+                    sample.direct = throughput * sky;
+                } else {
+                    sample.indirect = throughput * sky;
+                }
+                break;
+            }
+        }
+    }
+
+    stats::count_path(segments);
+    sample
+}
+
+fn camera(settings: &Settings, world: &HittableList, key: &Keyframe) -> Box<dyn Camera> {
+    let view_up = Vec3::new(0.0, 1.0, 0.0);
+    let aperture = 0.1;
+
+    match settings.projection {
+        Projection::Perspective => {
+            let mut cam = match &settings.lens {
+                Some(lens) => PerspectiveCamera::with_lens(
+                    key.origin,
+                    key.destination,
+                    view_up,
+                    lens,
+                    ASPECT_RATIO,
+                    key.focus_dist,
+                ),
+                None => PerspectiveCamera::new(
+                    key.origin,
+                    key.destination,
+                    view_up,
+                    key.fov,
+                    ASPECT_RATIO,
+                    aperture,
+                    key.focus_dist,
+                ),
+            };
+
+            if let Some(path) = &settings.aperture_mask {
+                let mask = ApertureMask::from_pgm(path).unwrap_or_else(|e| exit_with_error(e));
+                cam.set_aperture(Aperture::Mask(mask));
+            } else if let Some(blades) = settings.aperture_blades {
+                cam.set_aperture(Aperture::Polygon(blades));
+            }
+
+            if let Some(pixel) = settings.autofocus {
+                // Pixel rows count down from the top, film rows up from the bottom.
+                let (width, height) = image_size(settings.projection);
+                let film = (pixel.x as Float + 0.5, (height - 1 - pixel.y) as Float + 0.5);
+                let (s, t) = film_to_screen(film, (width, height));
+                match cam.autofocus(world, s, t) {
+                    Some(focus_dist) => eprintln!("Autofocus distance: {}", focus_dist),
+                    None => eprintln!("Autofocus missed the scene, keeping focus distance {}", key.focus_dist),
+                }
+            }
+
+            Box::new(cam)
+        }
+        Projection::Orthographic => {
+            let fov = Float::from(Radians::from(key.fov));
+            let viewport_height = 2.0 * key.focus_dist * (fov / 2.0).tan();
+            Box::new(OrthographicCamera::new(key.origin, key.destination, view_up, viewport_height, ASPECT_RATIO))
+        }
+        Projection::Fisheye(mapping) => Box::new(FisheyeCamera::new(
+            key.origin,
+            key.destination,
+            view_up,
+            key.fov,
+            ASPECT_RATIO,
+            mapping,
+        )),
+        Projection::Equirectangular => Box::new(EquirectangularCamera::new(key.origin, key.destination, view_up)),
+    }
+}
+
+fn sample_pixel(
+    world: &HittableList,
+    cam: &dyn Camera,
+    sampler: &mut dyn Sampler,
+    (i, j): (u32, u32),
+    index: u32,
+    size: (u32, u32),
+) -> ((Float, Float), PathSample) {
+    sampler.start_pixel_sample((i, j), index);
+    let (du, dv) = sampler.get_2d();
+    let (x, y) = (i as Float + du, j as Float + dv);
+    let (u, v) = film_to_screen((x, y), size);
+    let sample = match cam.get_ray(u, v, sampler.get_2d()) {
+        Some(ray) => trace(&ray, world, sampler),
+        None => PathSample::default(),
+    };
+    ((x, y), sample)
+}
+
+struct Rendered {
+    size: (u32, u32),
+    pixels: Vec<Color>,
+    noisy: Option<Vec<Color>>,
+    aovs: Vec<AovImage>,
+}
+
+fn render(settings: &Settings, world: &HittableList, cam: &dyn Camera, stats: &mut Stats) -> Rendered {
+    let (width, height) = image_size(settings.projection);
+    let progress_bar = ProgressBar::new(50, (width * height) as u64, settings.progress);
+    let mut sampler = settings.sampler.build(SAMPLES_PER_PIXEL);
+    let filter_radius = settings.filter_radius.unwrap_or_else(|| settings.filter.default_radius());
+    let mut film = Film::new(width, height, settings.filter.build(filter_radius));
+    let mut aovs = settings.aovs.clone();
+    if settings.denoise {
+        aovs.extend([Aov::Albedo, Aov::Normal].iter().filter(|aov| !settings.aovs.contains(aov)));
+    }
+    let mut aov_film = if aovs.is_empty() {
+        None
+    } else {
+        Some(AovFilm::new(&aovs, width, height, || settings.filter.build(filter_radius)))
+    };
+
+    stats.time("render", || {
+        for j in (0..height).rev() {
+            for i in 0..width {
+                for s in 0..SAMPLES_PER_PIXEL {
+                    let ((x, y), sample) = sample_pixel(world, cam, sampler.as_mut(), (i, j), s, (width, height));
+                    film.add_sample(x, y, sample.color());
+                    if let Some(aov_film) = &mut aov_film {
+                        aov_film.add_sample((i, j), (x, y), &sample);
+                    }
+                }
+                progress_bar.advance(1);
+            }
+        }
+        progress_bar.finish();
+    });
+
+    let pixels = film.resolve();
+    let mut aovs = aov_film.map_or_else(Vec::new, |aov_film| aov_film.resolve());
+    if !settings.denoise {
+        return Rendered { size: (width, height), pixels, noisy: None, aovs };
+    }
+
+    eprintln!("\nDenoising...");
+    let feature = |aov| &aovs.iter().find(|image| image.aov == aov).unwrap().pixels;
+    let denoised = stats.time("denoise", || {
+        Denoiser::default().denoise(width, height, &pixels, feature(Aov::Albedo), feature(Aov::Normal))
+    });
+    aovs.retain(|image| settings.aovs.contains(&image.aov));
+
+    Rendered {
+        size: (width, height),
+        pixels: denoised,
+        noisy: if settings.keep_noisy { Some(pixels) } else { None },
+        aovs,
+    }
+}
+
+fn write_file(
+    path: &Path,
+    settings: &ImageSettings,
+    (width, height): (u32, u32),
+    pixels: &[Color],
+    aovs: &[AovImage],
+) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    output::write_image(&mut out, settings, width, height, pixels, aovs)?;
+    out.flush()?;
+
+    if settings.format != ImageFormat::Exr {
+        for image in aovs {
+            let post_process = if image.aov.is_radiance() { settings.post_process } else { PostProcess::default() };
+            let aov_settings = ImageSettings { post_process, ..*settings };
+            let sidecar = output::sidecar_path(path, image.aov.name());
+            write_file(&sidecar, &aov_settings, (width, height), &image.display(), &[])?;
+        }
+    }
+    Ok(())
+}
+
+fn write_rendered(path: &Path, settings: &ImageSettings, rendered: &Rendered) -> io::Result<()> {
+    write_file(path, settings, rendered.size, &rendered.pixels, &rendered.aovs)?;
+    if let Some(noisy) = &rendered.noisy {
+        write_file(&output::sidecar_path(path, "noisy"), settings, rendered.size, noisy, &[])?;
+    }
+    Ok(())
+}
+
+fn render_animation(settings: &Settings, world: &HittableList, path: &CameraPath, stats: &mut Stats) -> io::Result<()> {
+    let base_seed = settings.seed.unwrap_or(0);
+
+    for (frame, key) in path.frames(settings.frames).enumerate() {
+        eprintln!("\nFrame {}/{}", frame + 1, settings.frames);
+        random::seed(base_seed.wrapping_add(frame as u64));
+
+        let cam = camera(settings, world, &key);
+        let rendered = render(settings, world, cam.as_ref(), stats);
+
+        let file_name = format!("frame_{:04}.{}", frame + 1, settings.frame_format.extension());
+        let path = Path::new(&settings.output_dir).join(file_name);
+        stats.time("output", || write_rendered(&path, &settings.image_settings(settings.frame_format), &rendered))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "preview")]
+fn run_preview(settings: &Settings, world: &HittableList, view: &Keyframe) -> io::Result<()> {
+    preview::run(settings, world, view)
+}
+
+#[cfg(not(feature = "preview"))]
+fn run_preview(_settings: &Settings, _world: &HittableList, _view: &Keyframe) -> io::Result<()> {
+    Err(io::Error::other(
+        "`--preview` needs the `preview` feature, rebuild with `cargo run --release --features preview`",
+    ))
+}
+
+/// View of the built-in scenes, which don't bring their own.
+fn default_view(settings: &Settings) -> Keyframe {
+    Keyframe {
+        time: 0.0,
+        origin: Point3::new(13.0, 2.0, 3.0),
+        destination: Point3::new(0.0, 0.0, 0.0),
+        fov: settings.projection.default_fov(),
+        focus_dist: 10.0,
+    }
+}
+
+fn exit_with_error(error: impl std::fmt::Display) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(1);
+}
+
+/// Renders what the command line asks for, exiting with an error message if it can't.
+pub fn run() {
+    let settings = Settings::from_args(std::env::args().skip(1)).unwrap_or_else(|e| exit_with_error(e));
+
+    if let Some(seed) = settings.seed {
+        random::seed(seed);
+    }
+
+    let mut stats = Stats::new();
+    let loaded = stats
+        .time("scene", || match &settings.input {
+            Some(path) => scene::load(path),
+            None => scene::build(settings.scene, settings.heightmap.as_deref()),
+        })
+        .unwrap_or_else(|e| exit_with_error(e));
+    // Options saved with the scene come first, so the ones given on the command line override them.
+    let settings = if loaded.options.is_empty() {
+        settings
+    } else {
+        let args = loaded.options.iter().cloned().chain(std::env::args().skip(1));
+        Settings::from_args(args).unwrap_or_else(|e| exit_with_error(e))
+    };
+    let world = loaded.world;
+    let mut view = loaded.view.unwrap_or_else(|| default_view(&settings));
+    if let Some(fov) = settings.fov {
+        view.fov = fov;
+    }
+    if let Some(path) = &settings.export {
+        scene_file::write(path, &world, &loaded.materials, &view, &settings).unwrap_or_else(|e| exit_with_error(e));
+    }
+
+    let result = match &settings.animation {
+        // `--preview` and `--animation` are mutually exclusive.
+        None if settings.preview => run_preview(&settings, &world, &view),
+        Some(animation) => {
+            let path = match animation.as_str() {
+                "turntable" => Ok(CameraPath::turntable(&view, 8, settings.interpolation)),
+                file => CameraPath::from_file(file, settings.interpolation),
+            };
+            let path = path.unwrap_or_else(|e| exit_with_error(e));
+            render_animation(&settings, &world, &path, &mut stats)
+        }
+        None => {
+            let cam = camera(&settings, &world, &view);
+            let rendered = render(&settings, &world, cam.as_ref(), &mut stats);
+
+            let image_settings = settings.image_settings(settings.output_format);
+            stats.time("output", || match &settings.output {
+                Some(path) => write_rendered(Path::new(path), &image_settings, &rendered),
+                None => {
+                    let stdout = io::stdout();
+                    let mut out = BufWriter::new(stdout.lock());
+                    let ((width, height), pixels, aovs) = (rendered.size, &rendered.pixels, &rendered.aovs);
+                    output::write_image(&mut out, &image_settings, width, height, pixels, aovs)
+                        .and_then(|_| out.flush())
+                }
+            })
+        }
+    };
+
+    if let Err(e) = result {
+        exit_with_error(e);
+    }
+    eprint!("\nDone!");
+
+    let report = stats.report();
+    if settings.stats {
+        eprint!("\n\n{}", report.summary());
+    }
+    if let Some(path) = &settings.stats_json {
+        fs::write(path, report.to_json()).unwrap_or_else(|e| exit_with_error(format!("can't write `{}`: {}", path, e)));
+    }
+}
@@ -1,8 +1,12 @@
+use crate::float::Float;
 use crate::color::Color;
 use crate::ray::Ray;
 use crate::hit::HitRecord;
 use crate::vec3::Vec3;
-use rand::Rng;
+use crate::texture::{Texture, SolidColor};
+use crate::sampler::Sampler;
+use crate::scene_file;
+use std::rc::Rc;
 
 pub(crate) struct AttenuatedRay {
     pub attenuation: Color,
@@ -16,46 +20,66 @@ impl AttenuatedRay {
 }
 
 pub(crate) trait Material {
-    fn scatter(&self, ray: &Ray, hit_rec: &HitRecord) -> Option<AttenuatedRay>;
+    fn scatter(&self, ray: &Ray, hit_rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<AttenuatedRay>;
+    fn albedo(&self, hit_rec: &HitRecord) -> Color;
+
+    /// Description following the name in a scene file `material` statement.
+    fn export(&self) -> Option<String> {
+        None
+    }
 }
 
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Clone)]
 pub(crate) struct Lambertian {
-    albedo: Color,
+    albedo: Rc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
+        Lambertian { albedo: Rc::new(SolidColor::new(albedo)) }
+    }
+
+    pub fn textured(albedo: Rc<dyn Texture>) -> Self {
         Lambertian { albedo }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, ray: &Ray, hit_rec: &HitRecord) -> Option<AttenuatedRay> {
-        let scatter_direction = hit_rec.normal + Vec3::random_unit_vector();
-        Some(AttenuatedRay::new(
-            self.albedo,
-            Ray::new(hit_rec.p, scatter_direction),
-        ))
+    fn scatter(&self, _ray: &Ray, hit_rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<AttenuatedRay> {
+        let scatter_direction = hit_rec.normal + Vec3::sample_unit_vector(sampler.get_2d());
+        Some(AttenuatedRay::new(self.albedo(hit_rec), hit_rec.spawn_ray(scatter_direction)))
+    }
+
+    fn albedo(&self, hit_rec: &HitRecord) -> Color {
+        let albedo = self.albedo.value(hit_rec.u, hit_rec.v, &hit_rec.p);
+        match hit_rec.vertex_color {
+            Some(tint) => (Vec3::from(albedo) * Vec3::from(tint)).into(),
+            None => albedo,
+        }
+    }
+
+    fn export(&self) -> Option<String> {
+        Some(format!("lambertian {}", self.albedo.export()?))
     }
 }
 
 #[derive(Copy, Clone, Default, Debug)]
 pub(crate) struct Metal {
     albedo: Color,
-    roughness: f32,
+    roughness: Float,
 }
 
 impl Metal {
-    pub fn new(albedo: Color, roughness: f32) -> Self {
-        Metal { albedo, roughness: f32::min(roughness, 1.0) }
+    pub fn new(albedo: Color, roughness: Float) -> Self {
+        Metal { albedo, roughness: Float::min(roughness, 1.0) }
     }
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray: &Ray, hit_rec: &HitRecord) -> Option<AttenuatedRay> {
+    fn scatter(&self, ray: &Ray, hit_rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<AttenuatedRay> {
         let reflected = Vec3::reflect(&ray.direction.unit_vec(), &hit_rec.normal);
-        let scattered = Ray::new(hit_rec.p, reflected + self.roughness * Vec3::random_in_unit_sphere());
+        let fuzz = Vec3::sample_in_unit_sphere(sampler.get_2d(), sampler.get_1d());
+        let scattered = hit_rec.spawn_ray(reflected + self.roughness * fuzz);
         let attenuation = self.albedo;
 
         if Vec3::dot(&scattered.direction, &hit_rec.normal) <= 0.0 {
@@ -63,19 +87,70 @@ impl Material for Metal {
         }
         Some(AttenuatedRay::new(attenuation, scattered))
     }
+
+    fn albedo(&self, _hit_rec: &HitRecord) -> Color {
+        self.albedo
+    }
+
+    fn export(&self) -> Option<String> {
+        Some(format!("metal {} {}", scene_file::color(self.albedo), self.roughness))
+    }
+}
+
+/// A diffuse base under a clear dielectric coat. The coat reflects the Fresnel share of the light untinted,
+/// blurred by `roughness` like a metal, and the rest scatters off the base.
+#[derive(Copy, Clone, Default, Debug)]
+pub(crate) struct Plastic {
+    albedo: Color,
+    refraction_idx: Float,
+    roughness: Float,
+}
+
+impl Plastic {
+    pub fn new(albedo: Color, refraction_idx: Float, roughness: Float) -> Self {
+        Plastic { albedo, refraction_idx, roughness: Float::min(roughness, 1.0) }
+    }
+}
+
+impl Material for Plastic {
+    fn scatter(&self, ray: &Ray, hit_rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<AttenuatedRay> {
+        let unit_direction = ray.direction.unit_vec();
+        let cos_theta = Float::min(Vec3::dot(&-unit_direction, &hit_rec.normal), 1.0);
+
+        if sampler.get_1d() < Dielectric::schlick(cos_theta, self.refraction_idx) {
+            let reflected = Vec3::reflect(&unit_direction, &hit_rec.normal);
+            let fuzz = Vec3::sample_in_unit_sphere(sampler.get_2d(), sampler.get_1d());
+            let scattered = hit_rec.spawn_ray(reflected + self.roughness * fuzz);
+            if Vec3::dot(&scattered.direction, &hit_rec.normal) <= 0.0 {
+                return None;
+            }
+            return Some(AttenuatedRay::new(Color::new(1.0, 1.0, 1.0), scattered));
+        }
+
+        let scatter_direction = hit_rec.normal + Vec3::sample_unit_vector(sampler.get_2d());
+        Some(AttenuatedRay::new(self.albedo, hit_rec.spawn_ray(scatter_direction)))
+    }
+
+    fn albedo(&self, _hit_rec: &HitRecord) -> Color {
+        self.albedo
+    }
+
+    fn export(&self) -> Option<String> {
+        Some(format!("plastic {} {} {}", scene_file::color(self.albedo), self.refraction_idx, self.roughness))
+    }
 }
 
 #[derive(Copy, Clone, Default, Debug)]
 pub(crate) struct Dielectric {
-    refraction_idx: f32,
+    refraction_idx: Float,
 }
 
 impl Dielectric {
-    pub fn new(refraction_idx: f32) -> Self {
+    pub fn new(refraction_idx: Float) -> Self {
         Dielectric { refraction_idx }
     }
 
-    fn schlick(cosine: f32, refraction_idx: f32) -> f32 {
+    fn schlick(cosine: Float, refraction_idx: Float) -> Float {
         let mut r0 = (1.0 - refraction_idx) / (1.0 + refraction_idx);
         r0 *= r0;
         r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
@@ -83,20 +158,19 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray: &Ray, hit_rec: &HitRecord) -> Option<AttenuatedRay> {
+    fn scatter(&self, ray: &Ray, hit_rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<AttenuatedRay> {
         let etai_over_etat = if hit_rec.front_face {
             1.0 / self.refraction_idx
         } else {
             self.refraction_idx
         };
 
-        let mut rng = rand::thread_rng();
         let unit_direction = ray.direction.unit_vec();
-        let cos_theta = f32::min(Vec3::dot(&-unit_direction, &hit_rec.normal), 1.0);
-        let sin_theta = f32::sqrt(1.0 - cos_theta * cos_theta);
+        let cos_theta = Float::min(Vec3::dot(&-unit_direction, &hit_rec.normal), 1.0);
+        let sin_theta = Float::sqrt(1.0 - cos_theta * cos_theta);
         let reflect_probability = Dielectric::schlick(cos_theta, etai_over_etat);
 
-        let direction = if etai_over_etat * sin_theta > 1.0 || rng.gen::<f32>() < reflect_probability {
+        let direction = if etai_over_etat * sin_theta > 1.0 || sampler.get_1d() < reflect_probability {
             Vec3::reflect(&unit_direction, &hit_rec.normal)
         } else {
             Vec3::refract(unit_direction, hit_rec.normal, etai_over_etat)
@@ -104,7 +178,48 @@ impl Material for Dielectric {
 
         Some(AttenuatedRay::new(
             Color::new(1.0, 1.0, 1.0),
-            Ray::new(hit_rec.p, direction),
+            hit_rec.spawn_ray(direction),
         ))
     }
+
+    fn albedo(&self, _hit_rec: &HitRecord) -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    fn export(&self) -> Option<String> {
+        Some(format!("dielectric {}", self.refraction_idx))
+    }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point3::Point3;
+    use crate::random;
+    use crate::sampler::SamplerKind;
+
+    /// Share of rays the plastic's coat reflects, arriving at `cos_theta` to the normal.
+    fn coat_reflectance(cos_theta: Float) -> Float {
+        random::seed(7);
+        let material: Rc<dyn Material> = Rc::new(Plastic::new(Color::new(0.5, 0.5, 0.5), 1.5, 0.0));
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+        let hit_rec = HitRecord::new(Point3::new(0.0, 0.0, 0.0), normal, 1.0, (0.0, 0.0), material.clone());
+        let direction = Vec3::new(Float::sqrt(1.0 - cos_theta * cos_theta), -cos_theta, 0.0);
+        let ray = Ray::new(Point3::from(-direction), direction);
+
+        let mut sampler = SamplerKind::Uniform.build(1);
+        let count = 10_000;
+        let reflected = (0..count)
+            .filter_map(|_| material.scatter(&ray, &hit_rec, sampler.as_mut()))
+            .filter(|r| r.attenuation.r() == 1.0)
+            .count();
+        reflected as Float / count as Float
+    }
+
+    #[test]
+    fn plastic_coat_reflects_more_at_grazing_angles() {
+        assert!((coat_reflectance(1.0) - 0.04).abs() < 0.01);
+        assert!(coat_reflectance(0.05) > 0.5);
+    }
+}
+
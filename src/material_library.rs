@@ -0,0 +1,83 @@
+use crate::color::Color;
+use crate::material::{Metal, Plastic, Dielectric, Material};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+/// Materials keyed by name. Every lookup of a name returns the same shared instance.
+pub(crate) struct MaterialLibrary {
+    materials: BTreeMap<String, Rc<dyn Material>>,
+}
+
+impl MaterialLibrary {
+    /// Library holding the presets: dielectrics with their refractive indices, polished metals with the
+    /// reflectance of the pure metal, and glossy plastics with a clear coat over a colored base.
+    pub fn new() -> Self {
+        let presets: Vec<(&str, Rc<dyn Material>)> = vec![
+            ("glass", Rc::new(Dielectric::new(1.5))),
+            ("water", Rc::new(Dielectric::new(1.333))),
+            ("diamond", Rc::new(Dielectric::new(2.418))),
+            ("gold", Rc::new(Metal::new(Color::new(1.0, 0.766, 0.336), 0.05))),
+            ("silver", Rc::new(Metal::new(Color::new(0.972, 0.960, 0.915), 0.05))),
+            ("copper", Rc::new(Metal::new(Color::new(0.955, 0.637, 0.538), 0.05))),
+            ("plastic-white", Rc::new(Plastic::new(Color::new(0.8, 0.8, 0.8), 1.5, 0.1))),
+            ("plastic-black", Rc::new(Plastic::new(Color::new(0.04, 0.04, 0.04), 1.5, 0.1))),
+            ("plastic-red", Rc::new(Plastic::new(Color::new(0.7, 0.1, 0.1), 1.5, 0.1))),
+            ("plastic-green", Rc::new(Plastic::new(Color::new(0.1, 0.6, 0.15), 1.5, 0.1))),
+            ("plastic-blue", Rc::new(Plastic::new(Color::new(0.1, 0.2, 0.7), 1.5, 0.1))),
+            ("plastic-yellow", Rc::new(Plastic::new(Color::new(0.8, 0.65, 0.1), 1.5, 0.1))),
+        ];
+        MaterialLibrary { materials: presets.into_iter().map(|(name, material)| (name.to_string(), material)).collect() }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Rc<dyn Material>> {
+        self.materials.get(name).cloned()
+    }
+
+    /// Looks up a material the code knows to exist, such as a preset.
+    pub fn preset(&self, name: &str) -> Rc<dyn Material> {
+        self.get(name).unwrap_or_else(|| panic!("no material named `{}`", name))
+    }
+
+    /// Adds or replaces the material called `name`.
+    pub fn insert(&mut self, name: &str, material: Rc<dyn Material>) {
+        self.materials.insert(name.to_string(), material);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Rc<dyn Material>)> {
+        self.materials.iter().map(|(name, material)| (name.as_str(), material))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookups_share_one_instance() {
+        let library = MaterialLibrary::new();
+        assert!(Rc::ptr_eq(&library.preset("diamond"), &library.preset("diamond")));
+        assert!(!Rc::ptr_eq(&library.preset("glass"), &library.preset("water")));
+        assert!(library.get("unobtainium").is_none());
+    }
+
+    #[test]
+    fn presets_describe_their_physics() {
+        let library = MaterialLibrary::new();
+        let describe = |name: &str| library.preset(name).export().unwrap();
+        assert_eq!("dielectric 1.5", describe("glass"));
+        assert_eq!("dielectric 1.333", describe("water"));
+        assert_eq!("dielectric 2.418", describe("diamond"));
+        assert!(describe("copper").starts_with("metal 0.955 0.637 0.538"));
+        assert_eq!("plastic 0.7 0.1 0.1 1.5 0.1", describe("plastic-red"));
+        assert!(library.iter().filter(|(name, _)| name.starts_with("plastic-")).all(|(_, m)| m.export().unwrap().starts_with("plastic")));
+    }
+
+    #[test]
+    fn inserted_materials_replace_presets() {
+        let mut library = MaterialLibrary::new();
+        let tinted: Rc<dyn Material> = Rc::new(Dielectric::new(1.52));
+        library.insert("glass", tinted.clone());
+        library.insert("crown-glass", tinted.clone());
+        assert!(Rc::ptr_eq(&library.preset("glass"), &library.preset("crown-glass")));
+    }
+}
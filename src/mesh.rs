@@ -0,0 +1,191 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::point3::Point3;
+use crate::hit::{Hit, HitRecord};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::triangle::intersect_triangle;
+use std::rc::Rc;
+
+/// Vertex attributes and triangle indices of a mesh. Optional attributes are either empty or hold one
+/// entry per position.
+#[derive(Default)]
+pub(crate) struct MeshData {
+    pub positions: Vec<Point3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<(Float, Float)>,
+    pub colors: Vec<Color>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+/// Indexed triangle mesh with optional smooth normals, texture coordinates and vertex colors.
+pub(crate) struct Mesh {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(Float, Float)>,
+    colors: Vec<Color>,
+    triangles: Vec<[usize; 3]>,
+    bvh: Bvh,
+    material: Rc<dyn Material>,
+}
+
+impl Mesh {
+    pub fn new(data: MeshData, material: Rc<dyn Material>) -> Result<Self, String> {
+        let MeshData { positions, normals, uvs, colors, triangles } = data;
+        let attributes = [("normals", normals.len()), ("texture coordinates", uvs.len()), ("colors", colors.len())];
+        for (name, len) in attributes {
+            if len != 0 && len != positions.len() {
+                return Err(format!("mesh has {} {} for {} vertices", len, name, positions.len()));
+            }
+        }
+        if let Some(index) = triangles.iter().flatten().find(|i| **i >= positions.len()) {
+            return Err(format!("triangle index {} is out of range for {} vertices", index, positions.len()));
+        }
+
+        let boxes: Vec<Aabb> = triangles
+            .iter()
+            .map(|[a, b, c]| {
+                // Pad so axis-aligned triangles don't end up in zero-thickness boxes.
+                let pad = Vec3::new(1e-4, 1e-4, 1e-4);
+                let bounds = Aabb::new(positions[*a], positions[*b]).surrounding(&Aabb::new(positions[*c], positions[*c]));
+                Aabb::new(bounds.min - pad, bounds.max + pad)
+            })
+            .collect();
+        let bvh = Bvh::new(&boxes);
+
+        Ok(Mesh { positions, normals, uvs, colors, triangles, bvh, material })
+    }
+
+    fn vertices(&self, triangle: usize) -> [Point3; 3] {
+        self.triangles[triangle].map(|i| self.positions[i])
+    }
+}
+
+impl Hit for Mesh {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let (t, triangle, b1, b2) = self.bvh.hit(ray, t_min, t_max, |triangle, t_max| {
+            let (t, b1, b2) = intersect_triangle(ray, self.vertices(triangle), t_min, t_max)?;
+            Some((t, (t, triangle, b1, b2)))
+        })?;
+
+        let [a, b, c] = self.triangles[triangle];
+        let weights = [1.0 - b1 - b2, b1, b2];
+        let outward_normal = if self.normals.is_empty() {
+            let [v0, v1, v2] = self.vertices(triangle);
+            (Vec3::from(v1) - Vec3::from(v0)).cross(&(Vec3::from(v2) - Vec3::from(v0))).unit_vec()
+        } else {
+            (weights[0] * self.normals[a] + weights[1] * self.normals[b] + weights[2] * self.normals[c]).unit_vec()
+        };
+        let uv = if self.uvs.is_empty() {
+            (b1, b2)
+        } else {
+            let (uvs, w) = ([self.uvs[a], self.uvs[b], self.uvs[c]], weights);
+            (w[0] * uvs[0].0 + w[1] * uvs[1].0 + w[2] * uvs[2].0, w[0] * uvs[0].1 + w[1] * uvs[1].1 + w[2] * uvs[2].1)
+        };
+
+        let mut rec = HitRecord::new(ray.at(t), outward_normal, t, uv, self.material.clone());
+        rec.set_face_normal(ray, &outward_normal);
+        if !self.colors.is_empty() {
+            let color = weights[0] * Vec3::from(self.colors[a]) + weights[1] * Vec3::from(self.colors[b]) + weights[2] * Vec3::from(self.colors[c]);
+            rec.vertex_color = Some(color.into());
+        }
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounds()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn quad(normals: Vec<Vec3>) -> Result<Mesh, String> {
+        let positions = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ];
+        let uvs = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let data = MeshData { positions, normals, uvs, triangles: vec![[0, 1, 2], [0, 2, 3]], ..MeshData::default() };
+        Mesh::new(data, Rc::new(Lambertian::new(Color::default())))
+    }
+
+    #[test]
+    fn flat_quad_interpolates_uv() {
+        let mesh = quad(Vec::new()).unwrap();
+        let ray = Ray::new(Point3::new(0.25, 0.75, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((rec.t - 3.0).abs() < 1e-6);
+        assert!((rec.normal.z() - 1.0).abs() < 1e-6);
+        assert!((rec.u - 0.25).abs() < 1e-6 && (rec.v - 0.75).abs() < 1e-6);
+        assert_eq!(2, mesh.triangles.len());
+    }
+
+    #[test]
+    fn vertex_normals_are_interpolated() {
+        let tilted = Vec3::new(1.0, 0.0, 1.0).unit_vec();
+        let normals = vec![Vec3::new(0.0, 0.0, 1.0), tilted, tilted, Vec3::new(0.0, 0.0, 1.0)];
+        let mesh = quad(normals).unwrap();
+        let ray = Ray::new(Point3::new(0.5, 0.5, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!(rec.normal.x() > 0.0 && rec.normal.x() < tilted.x());
+    }
+
+    #[test]
+    fn vertex_colors_are_interpolated() {
+        let data = MeshData {
+            positions: vec![Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0), Point3::new(0.0, 1.0, 0.0)],
+            colors: vec![Color::new(1.0, 0.0, 0.0), Color::new(0.0, 1.0, 0.0), Color::new(0.0, 0.0, 1.0)],
+            triangles: vec![[0, 1, 2]],
+            ..MeshData::default()
+        };
+        let mesh = Mesh::new(data, Rc::new(Lambertian::new(Color::new(1.0, 1.0, 1.0)))).unwrap();
+        let ray = Ray::new(Point3::new(0.5, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = mesh.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        let albedo = rec.material.albedo(&rec);
+        assert!((albedo.r() - 0.25).abs() < 1e-6);
+        assert!((albedo.g() - 0.5).abs() < 1e-6);
+        assert!((albedo.b() - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn many_triangles_agree_with_brute_force() {
+        let mut positions = Vec::new();
+        let mut triangles = Vec::new();
+        for i in 0..200 {
+            let a = i as Float * 0.7;
+            let base = Point3::new(a.cos() * (1.0 + i as Float * 0.01), a.sin(), (i as Float * 0.37).sin());
+            let n = positions.len();
+            positions.extend([base, base + Vec3::new(0.3, 0.0, 0.1), base + Vec3::new(0.0, 0.3, -0.1)]);
+            triangles.push([n, n + 1, n + 2]);
+        }
+        let material = Rc::new(Lambertian::new(Color::default()));
+        let mesh = Mesh::new(MeshData { positions, triangles, ..MeshData::default() }, material).unwrap();
+
+        for k in 0..100 {
+            let a = k as Float * 0.13;
+            let ray = Ray::new(Point3::new(0.1 * a.sin(), 0.2 * a.cos(), 5.0), Vec3::new(a.cos(), a.sin(), -4.0));
+            let brute = (0..mesh.triangles.len())
+                .filter_map(|i| intersect_triangle(&ray, mesh.vertices(i), 0.001, Float::INFINITY))
+                .map(|(t, _, _)| t)
+                .fold(None, |best: Option<Float>, t| Some(best.map_or(t, |b| b.min(t))));
+            assert_eq!(brute, mesh.hit(&ray, 0.001, Float::INFINITY).map(|rec| rec.t));
+        }
+    }
+
+    #[test]
+    fn invalid_meshes_are_rejected() {
+        assert!(quad(vec![Vec3::new(0.0, 0.0, 1.0)]).is_err());
+        let material = Rc::new(Lambertian::new(Color::default()));
+        let data = MeshData { positions: vec![Point3::new(0.0, 0.0, 0.0)], triangles: vec![[0, 1, 2]], ..MeshData::default() };
+        let error = Mesh::new(data, material).err().unwrap();
+        assert_eq!("triangle index 1 is out of range for 1 vertices", error);
+    }
+}
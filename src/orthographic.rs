@@ -0,0 +1,42 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::camera::{Camera, orthonormal_basis};
+
+pub(crate) struct OrthographicCamera {
+    lower_left_corner: Point3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    direction: Vec3,
+}
+
+impl OrthographicCamera {
+    pub fn new(
+        origin: Point3,
+        destination: Point3,
+        view_up: Vec3,
+        viewport_height: Float,
+        aspect_ratio: Float,
+    ) -> Self {
+        let (u, v, w) = orthonormal_basis(origin, destination, view_up);
+        let horizontal = aspect_ratio * viewport_height * u;
+        let vertical = viewport_height * v;
+
+        OrthographicCamera {
+            lower_left_corner: origin - horizontal / 2.0 - vertical / 2.0,
+            horizontal,
+            vertical,
+            direction: -w,
+        }
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn get_ray(&self, u: Float, v: Float, _lens_sample: (Float, Float)) -> Option<Ray> {
+        Some(Ray::new(
+            self.lower_left_corner + u * self.horizontal + v * self.vertical,
+            self.direction,
+        ))
+    }
+}
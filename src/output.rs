@@ -0,0 +1,231 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::exr::{self, ExrChannel, ExrPrecision};
+use crate::aov::AovImage;
+use crate::tone_mapping::PostProcess;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum ImageFormat {
+    #[default]
+    Ppm,
+    Png,
+    Exr,
+    Hdr,
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ppm" => Ok(ImageFormat::Ppm),
+            "png" => Ok(ImageFormat::Png),
+            "exr" => Ok(ImageFormat::Exr),
+            "hdr" => Ok(ImageFormat::Hdr),
+            _ => Err(format!("unknown image format `{}`, expected one of: ppm, png, exr, hdr", s)),
+        }
+    }
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &str) -> Result<Self, String> {
+        Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .ok_or_else(|| format!("`{}` has no file extension to pick an image format from", path))?
+            .parse()
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+            ImageFormat::Exr => "exr",
+            ImageFormat::Hdr => "hdr",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct ImageSettings {
+    pub format: ImageFormat,
+    pub post_process: PostProcess,
+    pub exr_precision: ExrPrecision,
+}
+
+pub(crate) fn write_image(
+    out: &mut impl Write,
+    settings: &ImageSettings,
+    width: u32,
+    height: u32,
+    pixels: &[Color],
+    aovs: &[AovImage],
+) -> io::Result<()> {
+    let display = || pixels.iter().map(|p| settings.post_process.apply(*p)).collect::<Vec<_>>();
+
+    match settings.format {
+        ImageFormat::Ppm => write_ppm(out, width, height, &display()),
+        ImageFormat::Png => write_png(out, width, height, &display()),
+        ImageFormat::Hdr => write_hdr(out, width, height, pixels),
+        // EXR channels are always f32, which `Float` already is unless the `f64` feature is on.
+        #[allow(clippy::unnecessary_cast)]
+        ImageFormat::Exr => {
+            let precision = settings.exr_precision;
+            let channel = |name, f: fn(&Color) -> Float| {
+                ExrChannel::new(name, pixels.iter().map(|p| f(p) as f32).collect(), precision)
+            };
+            let mut channels = vec![channel("R", Color::r), channel("G", Color::g), channel("B", Color::b)];
+            for image in aovs {
+                // Depth, normals and IDs are data rather than colour, and halves would round IDs past 2048.
+                let precision = if image.aov.is_radiance() { precision } else { ExrPrecision::Float };
+                let components: [fn(&Color) -> Float; 3] = [Color::r, Color::g, Color::b];
+                for (name, component) in image.aov.channels().iter().zip(&components) {
+                    let values = image.pixels.iter().map(|p| component(p) as f32).collect();
+                    channels.push(ExrChannel::new(name, values, precision));
+                }
+            }
+            exr::write_exr(out, width, height, &mut channels)
+        }
+    }
+}
+
+pub(crate) fn sidecar_path(image: &Path, name: &str) -> PathBuf {
+    let stem = image.file_stem().and_then(|s| s.to_str()).unwrap_or("image");
+    let mut file_name = format!("{}.{}", stem, name);
+    if let Some(extension) = image.extension().and_then(|e| e.to_str()) {
+        file_name = format!("{}.{}", file_name, extension);
+    }
+    image.with_file_name(file_name)
+}
+
+pub(crate) fn write_ppm(out: &mut impl Write, width: u32, height: u32, pixels: &[Color]) -> io::Result<()> {
+    write!(out, "P3\n{} {}\n255\n", width, height)?;
+    for pixel in pixels {
+        writeln!(out, "{}", pixel)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn write_png(out: &mut impl Write, width: u32, height: u32, pixels: &[Color]) -> io::Result<()> {
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut raw = Vec::with_capacity(pixels.len() * 3 + height as usize);
+    for row in pixels.chunks(width as usize) {
+        raw.push(0);
+        for pixel in row {
+            raw.extend(pixel.0.iter().map(|c| (255.0 * c).clamp(0.0, 255.0) as u8));
+        }
+    }
+
+    out.write_all(b"\x89PNG\r\n\x1a\n")?;
+    write_png_chunk(out, b"IHDR", &ihdr)?;
+    write_png_chunk(out, b"IDAT", &zlib_stored(&raw))?;
+    write_png_chunk(out, b"IEND", &[])
+}
+
+pub(crate) fn write_hdr(out: &mut impl Write, width: u32, height: u32, pixels: &[Color]) -> io::Result<()> {
+    write!(out, "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", height, width)?;
+    for pixel in pixels {
+        out.write_all(&rgbe(pixel))?;
+    }
+    Ok(())
+}
+
+fn rgbe(color: &Color) -> [u8; 4] {
+    let max = color.r().max(color.g()).max(color.b());
+    if max < 1e-32 {
+        return [0; 4];
+    }
+
+    let exponent = max.log2().floor() as i32 + 1;
+    let scale = 256.0 / (2.0 as Float).powi(exponent);
+    let mantissa = |c: Float| (c.max(0.0) * scale).min(255.0) as u8;
+    [mantissa(color.r()), mantissa(color.g()), mantissa(color.b()), (exponent + 128) as u8]
+}
+
+fn write_png_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    out.write_all(&crc32(kind.iter().chain(data)).to_be_bytes())
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        out.push(if blocks.peek().is_none() { 1 } else { 0 });
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    !bytes.into_iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 }
+        })
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn rgbe_encoding() {
+        assert_eq!([128, 64, 0, 129], rgbe(&Color::new(1.0, 0.5, 0.0)));
+        assert_eq!([160, 0, 0, 131], rgbe(&Color::new(5.0, 0.0, -1.0)));
+        assert_eq!([0, 0, 0, 0], rgbe(&Color::default()));
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(Ok(ImageFormat::Exr), ImageFormat::from_path("out/render.EXR"));
+        assert!(ImageFormat::from_path("render").is_err());
+    }
+
+    #[test]
+    fn sidecar_file_path() {
+        assert_eq!(Path::new("out/render.depth.png"), sidecar_path(Path::new("out/render.png"), "depth"));
+    }
+
+    #[test]
+    fn png_layout() {
+        let mut png = Vec::new();
+        write_png(&mut png, 2, 1, &[Color::new(1.0, 0.0, 0.0), Color::new(0.0, 0.0, 2.0)]).unwrap();
+
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+        assert_eq!(b"IHDR", &png[12..16]);
+        assert_eq!(b"IEND", &png[png.len() - 8..png.len() - 4]);
+    }
+}
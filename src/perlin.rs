@@ -0,0 +1,141 @@
+use crate::float::Float;
+use crate::vec3::Vec3;
+use crate::point3::Point3;
+use crate::random;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+const POINT_COUNT: usize = 256;
+
+pub(crate) struct Perlin {
+    seed: u64,
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    /// Noise seeded from the global generator, so `--seed` fixes it too.
+    pub fn new() -> Self {
+        Perlin::with_seed(random::rng().gen())
+    }
+
+    /// Noise built from its own generator, which lets scene files rebuild the same noise from the seed.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| Vec3::new(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0)).unit_vec())
+            .collect();
+        Perlin {
+            seed,
+            gradients,
+            perm_x: Perlin::generate_permutation(&mut rng),
+            perm_y: Perlin::generate_permutation(&mut rng),
+            perm_z: Perlin::generate_permutation(&mut rng),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn noise(&self, p: &Point3) -> Float {
+        let floor = Vec3::new(p.x().floor(), p.y().floor(), p.z().floor());
+        let frac = Vec3::from(*p) - floor;
+
+        let mut corners = [[[Vec3::default(); 2]; 2]; 2];
+        for (di, plane) in corners.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    *corner = self.gradients[
+                        self.perm_x[Perlin::wrap(floor.x(), di)]
+                            ^ self.perm_y[Perlin::wrap(floor.y(), dj)]
+                            ^ self.perm_z[Perlin::wrap(floor.z(), dk)]
+                    ];
+                }
+            }
+        }
+
+        Perlin::interpolate(&corners, frac)
+    }
+
+    pub fn turbulence(&self, p: &Point3, octaves: u32) -> Float {
+        let mut p = *p;
+        let mut weight = 1.0;
+        let mut accum = 0.0;
+
+        for _ in 0..octaves {
+            accum += weight * self.noise(&p);
+            weight *= 0.5;
+            p = (2.0 * Vec3::from(p)).into();
+        }
+
+        accum.abs()
+    }
+
+    fn wrap(floor: Float, offset: usize) -> usize {
+        (floor as i32 + offset as i32) as usize & (POINT_COUNT - 1)
+    }
+
+    fn generate_permutation(rng: &mut SmallRng) -> Vec<usize> {
+        let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
+        perm.shuffle(rng);
+        perm
+    }
+
+    fn interpolate(corners: &[[[Vec3; 2]; 2]; 2], frac: Vec3) -> Float {
+        let hermite = Vec3::new(
+            frac.x() * frac.x() * (3.0 - 2.0 * frac.x()),
+            frac.y() * frac.y() * (3.0 - 2.0 * frac.y()),
+            frac.z() * frac.z() * (3.0 - 2.0 * frac.z()),
+        );
+
+        let mut accum = 0.0;
+        for (i, plane) in corners.iter().enumerate() {
+            for (j, row) in plane.iter().enumerate() {
+                for (k, gradient) in row.iter().enumerate() {
+                    let (i, j, k) = (i as Float, j as Float, k as Float);
+                    let weight = Vec3::new(frac.x() - i, frac.y() - j, frac.z() - k);
+                    accum += (i * hermite.x() + (1.0 - i) * (1.0 - hermite.x()))
+                        * (j * hermite.y() + (1.0 - j) * (1.0 - hermite.y()))
+                        * (k * hermite.z() + (1.0 - k) * (1.0 - hermite.z()))
+                        * Vec3::dot(gradient, &weight);
+                }
+            }
+        }
+        accum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_zero_on_lattice_points() {
+        let perlin = Perlin::new();
+        for p in &[Point3::new(0.0, 0.0, 0.0), Point3::new(3.0, -2.0, 7.0)] {
+            assert!(perlin.noise(p).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn noise_is_bounded() {
+        let perlin = Perlin::new();
+        for i in 0..1000 {
+            let x = i as Float * 0.137;
+            let n = perlin.noise(&Point3::new(x, 0.5 * x, -x));
+            assert!((-1.0..=1.0).contains(&n));
+        }
+    }
+
+    #[test]
+    fn same_seed_same_noise() {
+        let (a, b, c) = (Perlin::with_seed(7), Perlin::with_seed(7), Perlin::with_seed(8));
+        let p = Point3::new(0.3, 1.7, -2.2);
+        assert_eq!(a.noise(&p), b.noise(&p));
+        assert_ne!(a.noise(&p), c.noise(&p));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::float::Float;
+use std::fs;
+
+/// A grayscale netpbm image with samples normalized to `0.0..=1.0`, stored row by row from the top.
+pub(crate) struct GrayImage {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<Float>,
+}
+
+pub(crate) fn read(path: &str) -> Result<GrayImage, String> {
+    let bytes = fs::read(path).map_err(|e| format!("can't read `{}`: {}", path, e))?;
+    parse(&bytes).map_err(|e| format!("`{}`: {}", path, e))
+}
+
+fn parse(bytes: &[u8]) -> Result<GrayImage, String> {
+    let mut header = PgmHeader { bytes, pos: 0 };
+
+    let magic = header.token()?;
+    let width: usize = header.number()?;
+    let height: usize = header.number()?;
+    let max_value: usize = header.number()?;
+    if max_value == 0 || max_value > 255 {
+        return Err(format!("unsupported max value {}", max_value));
+    }
+
+    let values: Vec<usize> = match magic.as_str() {
+        "P2" => (0..width * height)
+            .map(|_| header.number())
+            .collect::<Result<_, _>>()?,
+        "P5" => bytes
+            .get(header.pos + 1..header.pos + 1 + width * height)
+            .ok_or_else(|| "truncated pixel data".to_string())?
+            .iter()
+            .map(|b| *b as usize)
+            .collect(),
+        _ => return Err("expected a P2 or P5 PGM image".to_string()),
+    };
+
+    Ok(GrayImage {
+        width,
+        height,
+        values: values.into_iter().map(|v| v as Float / max_value as Float).collect(),
+    })
+}
+
+struct PgmHeader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PgmHeader<'a> {
+    fn token(&mut self) -> Result<String, String> {
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'#') => {
+                    while !matches!(self.bytes.get(self.pos), Some(b'\n') | None) {
+                        self.pos += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+
+        let start = self.pos;
+        while matches!(self.bytes.get(self.pos), Some(b) if !b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err("unexpected end of PGM data".to_string());
+        }
+        Ok(String::from_utf8_lossy(&self.bytes[start..self.pos]).into_owned())
+    }
+
+    fn number(&mut self) -> Result<usize, String> {
+        let token = self.token()?;
+        token.parse().map_err(|_| format!("invalid number `{}` in PGM data", token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_and_binary_agree() {
+        let ascii = parse(b"P2\n# comment\n2 1\n255\n0 255\n").unwrap();
+        let binary = parse(b"P5 2 1 255\n\x00\xff").unwrap();
+        assert_eq!((2, 1), (ascii.width, ascii.height));
+        assert_eq!(vec![0.0, 1.0], ascii.values);
+        assert_eq!(ascii.values, binary.values);
+    }
+
+    #[test]
+    fn truncated_data_is_rejected() {
+        assert!(parse(b"P5 2 2 255\n\x00").is_err());
+        assert!(parse(b"P2 2 2 255\n0 1 2").is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::hit::{Hit, HitRecord};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::scene_file::{self, SceneWriter};
+use std::rc::Rc;
+
+pub(crate) struct Plane {
+    point: Point3,
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    material: Rc<dyn Material>,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, material: Rc<dyn Material>) -> Self {
+        let normal = normal.unit_vec();
+        let (tangent, bitangent) = tangent_basis(&normal);
+        Plane { point, normal, tangent, bitangent, material }
+    }
+}
+
+impl Hit for Plane {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let t = plane_intersection(ray, self.point, &self.normal)?;
+        if t <= t_min || t >= t_max {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let offset = Vec3::from(p) - Vec3::from(self.point);
+        let uv = (Vec3::dot(&offset, &self.tangent), Vec3::dot(&offset, &self.bitangent));
+
+        let mut rec = HitRecord::new(p, self.normal, t, uv, self.material.clone());
+        rec.set_face_normal(ray, &self.normal);
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+
+    fn export(&self, scene: &mut SceneWriter) -> Option<()> {
+        let material = scene.material(&self.material)?;
+        scene.entry(format!("plane {} {} {}", scene_file::point(self.point), scene_file::vector(self.normal), material));
+        Some(())
+    }
+}
+
+pub(crate) fn plane_intersection(ray: &Ray, point: Point3, normal: &Vec3) -> Option<Float> {
+    let denominator = Vec3::dot(normal, &ray.direction);
+    if denominator.abs() < 1e-8 {
+        return None;
+    }
+    Some(Vec3::dot(&(Vec3::from(point) - Vec3::from(ray.origin)), normal) / denominator)
+}
+
+pub(crate) fn tangent_basis(normal: &Vec3) -> (Vec3, Vec3) {
+    let helper = if normal.x().abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let tangent = Vec3::cross(&helper, normal).unit_vec();
+    (tangent, Vec3::cross(normal, &tangent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn plane_hit_from_both_sides() {
+        let plane = Plane::new(Point3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 2.0, 0.0), Rc::new(Lambertian::new(Color::default())));
+
+        let rec = plane.hit(&Ray::new(Point3::new(3.0, 5.0, 0.0), Vec3::new(0.0, -2.0, 0.0)), 0.0, Float::INFINITY).unwrap();
+        assert_eq!(2.0, rec.t);
+        assert!(rec.front_face);
+        assert_eq!(1.0, rec.normal.y());
+
+        let rec = plane.hit(&Ray::new(Point3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0)), 0.0, Float::INFINITY).unwrap();
+        assert!(!rec.front_face);
+        assert_eq!(-1.0, rec.normal.y());
+
+        assert!(plane.hit(&Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(1.0, 0.0, 0.0)), 0.0, Float::INFINITY).is_none());
+        assert!(plane.bounding_box().is_none());
+    }
+}
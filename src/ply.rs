@@ -0,0 +1,332 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::point3::Point3;
+use crate::vec3::Vec3;
+use crate::mesh::MeshData;
+use std::fs;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Encoding {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+}
+
+impl FromStr for Scalar {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "char" | "int8" => Ok(Scalar::I8),
+            "uchar" | "uint8" => Ok(Scalar::U8),
+            "short" | "int16" => Ok(Scalar::I16),
+            "ushort" | "uint16" => Ok(Scalar::U16),
+            "int" | "int32" => Ok(Scalar::I32),
+            "uint" | "uint32" => Ok(Scalar::U32),
+            "float" | "float32" => Ok(Scalar::F32),
+            "double" | "float64" => Ok(Scalar::F64),
+            _ => Err(format!("unknown property type `{}`", s)),
+        }
+    }
+}
+
+struct Property {
+    name: String,
+    scalar: Scalar,
+    /// Type of the element count for list properties.
+    list: Option<Scalar>,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Reads property values from the body, in the file's encoding.
+struct Body<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    encoding: Encoding,
+}
+
+impl<'a> Body<'a> {
+    fn read(&mut self, scalar: Scalar) -> Result<f64, String> {
+        if self.encoding == Encoding::Ascii {
+            let token = self.token().ok_or_else(|| "unexpected end of file".to_string())?;
+            return token.parse().map_err(|_| format!("invalid number `{}`", token));
+        }
+
+        let bytes = self.bytes.get(self.pos..self.pos + scalar.size()).ok_or_else(|| "unexpected end of file".to_string())?;
+        self.pos += scalar.size();
+        let mut raw = [0u8; 8];
+        raw[..bytes.len()].copy_from_slice(bytes);
+        if self.encoding == Encoding::BinaryBigEndian {
+            raw[..bytes.len()].reverse();
+        }
+        Ok(match scalar {
+            Scalar::I8 => raw[0] as i8 as f64,
+            Scalar::U8 => raw[0] as f64,
+            Scalar::I16 => i16::from_le_bytes([raw[0], raw[1]]) as f64,
+            Scalar::U16 => u16::from_le_bytes([raw[0], raw[1]]) as f64,
+            Scalar::I32 => i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+            Scalar::U32 => u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+            Scalar::F32 => f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f64,
+            Scalar::F64 => f64::from_le_bytes(raw),
+        })
+    }
+
+    fn token(&mut self) -> Option<&'a str> {
+        while self.bytes.get(self.pos).is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos]).ok().filter(|t| !t.is_empty())
+    }
+}
+
+pub(crate) fn read(path: &str) -> Result<MeshData, String> {
+    let bytes = fs::read(path).map_err(|e| format!("can't read `{}`: {}", path, e))?;
+    parse(&bytes).map_err(|e| format!("`{}`: {}", path, e))
+}
+
+fn parse(bytes: &[u8]) -> Result<MeshData, String> {
+    let (encoding, elements, body_start) = parse_header(bytes)?;
+    let mut body = Body { bytes, pos: body_start, encoding };
+    let mut data = MeshData::default();
+
+    for element in &elements {
+        let property = |name: &str| element.properties.iter().position(|p| p.name == name);
+        for index in 0..element.count {
+            let mut values = Vec::with_capacity(element.properties.len());
+            for property in &element.properties {
+                let value = read_value(&mut body, property).map_err(|e| format!("{} in {} {}", e, element.name, index))?;
+                values.push(value);
+            }
+
+            match element.name.as_str() {
+                "vertex" => add_vertex(&mut data, element, &values)?,
+                "face" => {
+                    let indices = property("vertex_indices")
+                        .or_else(|| property("vertex_index"))
+                        .and_then(|i| values[i].list())
+                        .ok_or_else(|| "faces need a `vertex_indices` list".to_string())?;
+                    // Fan-triangulate polygons.
+                    for k in 1..indices.len().saturating_sub(1) {
+                        data.triangles.push([indices[0] as usize, indices[k] as usize, indices[k + 1] as usize]);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+    Ok(data)
+}
+
+fn read_value(body: &mut Body, property: &Property) -> Result<Value, String> {
+    match property.list {
+        Some(count_type) => {
+            let count = body.read(count_type)?;
+            (0..count as usize).map(|_| body.read(property.scalar)).collect::<Result<_, _>>().map(Value::List)
+        },
+        None => body.read(property.scalar).map(Value::Scalar),
+    }
+}
+
+enum Value {
+    Scalar(f64),
+    List(Vec<f64>),
+}
+
+impl Value {
+    fn scalar(&self) -> Option<f64> {
+        match self {
+            Value::Scalar(v) => Some(*v),
+            Value::List(_) => None,
+        }
+    }
+
+    fn list(&self) -> Option<&[f64]> {
+        match self {
+            Value::List(items) => Some(items),
+            Value::Scalar(_) => None,
+        }
+    }
+}
+
+fn add_vertex(data: &mut MeshData, element: &Element, values: &[Value]) -> Result<(), String> {
+    let property = |names: &[&str]| element.properties.iter().position(|p| names.contains(&p.name.as_str()));
+    let get = |names: &[&str]| property(names).and_then(|i| values[i].scalar());
+    let (x, y, z) = match (get(&["x"]), get(&["y"]), get(&["z"])) {
+        (Some(x), Some(y), Some(z)) => (x, y, z),
+        _ => return Err("vertices need `x`, `y` and `z` properties".to_string()),
+    };
+    data.positions.push(Point3::new(x as Float, y as Float, z as Float));
+
+    if let (Some(x), Some(y), Some(z)) = (get(&["nx"]), get(&["ny"]), get(&["nz"])) {
+        data.normals.push(Vec3::new(x as Float, y as Float, z as Float).unit_vec());
+    }
+    if let (Some(u), Some(v)) = (get(&["u", "s", "texture_u"]), get(&["v", "t", "texture_v"])) {
+        data.uvs.push((u as Float, v as Float));
+    }
+    if let (Some(r), Some(g), Some(b)) = (get(&["red"]), get(&["green"]), get(&["blue"])) {
+        // Integer colors span the type's range, floating point ones are already normalized.
+        let scale = match property(&["red"]).map(|i| element.properties[i].scalar) {
+            Some(Scalar::U8) => 1.0 / 255.0,
+            Some(Scalar::U16) => 1.0 / 65535.0,
+            _ => 1.0,
+        };
+        data.colors.push(Color::new((r * scale) as Float, (g * scale) as Float, (b * scale) as Float));
+    }
+    Ok(())
+}
+
+fn parse_header(bytes: &[u8]) -> Result<(Encoding, Vec<Element>, usize), String> {
+    const END: &[u8] = b"end_header";
+    let end = bytes
+        .windows(END.len())
+        .position(|w| w == END)
+        .ok_or_else(|| "missing `end_header`".to_string())?;
+    let body_start = bytes[end..].iter().position(|b| *b == b'\n').map_or(bytes.len(), |p| end + p + 1);
+    let header = std::str::from_utf8(&bytes[..end]).map_err(|_| "header is not valid text".to_string())?;
+
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err("not a PLY file".to_string());
+    }
+
+    let mut encoding = None;
+    let mut elements: Vec<Element> = Vec::new();
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] | ["comment", ..] | ["obj_info", ..] => {},
+            ["format", format, _version] => {
+                encoding = Some(match *format {
+                    "ascii" => Encoding::Ascii,
+                    "binary_little_endian" => Encoding::BinaryLittleEndian,
+                    "binary_big_endian" => Encoding::BinaryBigEndian,
+                    _ => return Err(format!("unknown format `{}`", format)),
+                });
+            },
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| format!("invalid element count `{}`", count))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_type, scalar, name] => elements
+                .last_mut()
+                .ok_or_else(|| format!("property `{}` outside of an element", name))?
+                .properties
+                .push(Property { name: name.to_string(), scalar: scalar.parse()?, list: Some(count_type.parse()?) }),
+            ["property", scalar, name] => elements
+                .last_mut()
+                .ok_or_else(|| format!("property `{}` outside of an element", name))?
+                .properties
+                .push(Property { name: name.to_string(), scalar: scalar.parse()?, list: None }),
+            _ => return Err(format!("malformed header line `{}`", line)),
+        }
+    }
+
+    let encoding = encoding.ok_or_else(|| "missing `format` line".to_string())?;
+    Ok((encoding, elements, body_start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "ply\nformat {}\ncomment made by hand\nelement vertex 4\nproperty float x\nproperty float y\nproperty float z\n\
+        property uchar red\nproperty uchar green\nproperty uchar blue\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n";
+
+    fn binary(format: &str, big_endian: bool) -> Vec<u8> {
+        let mut bytes = HEADER.replace("{}", format).into_bytes();
+        let vertices = [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+        for (i, v) in vertices.iter().enumerate() {
+            for c in v {
+                bytes.extend(if big_endian { c.to_be_bytes() } else { c.to_le_bytes() });
+            }
+            bytes.extend([255, 0, 51 * i as u8]);
+        }
+        bytes.push(4);
+        for i in 0..4i32 {
+            bytes.extend(if big_endian { i.to_be_bytes() } else { i.to_le_bytes() });
+        }
+        bytes
+    }
+
+    #[test]
+    fn ascii_quad_with_colors() {
+        let text = HEADER.replace("{}", "ascii 1.0") + "0 0 0 255 0 0\n1 0 0 255 0 51\n1 1 0 255 0 102\n0 1 0 255 0 153\n4 0 1 2 3\n";
+        let data = parse(text.as_bytes()).unwrap();
+        assert_eq!(4, data.positions.len());
+        assert_eq!(vec![[0, 1, 2], [0, 2, 3]], data.triangles);
+        assert!((data.colors[1].b() - 0.2).abs() < 1e-6);
+        assert!(data.normals.is_empty());
+    }
+
+    #[test]
+    fn binary_encodings_agree_with_ascii() {
+        let text = HEADER.replace("{}", "ascii 1.0") + "0 0 0 255 0 0\n1 0 0 255 0 51\n1 1 0 255 0 102\n0 1 0 255 0 153\n4 0 1 2 3\n";
+        let ascii = parse(text.as_bytes()).unwrap();
+        for (format, big_endian) in [("binary_little_endian 1.0", false), ("binary_big_endian 1.0", true)] {
+            let data = parse(&binary(format, big_endian)).unwrap();
+            assert_eq!(ascii.positions, data.positions);
+            assert_eq!(ascii.triangles, data.triangles);
+            assert!((data.colors[3].b() - 0.6).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn normals_and_extra_elements() {
+        let text = "ply\nformat ascii 1.0\nelement vertex 3\nproperty double x\nproperty double y\nproperty double z\n\
+            property float nx\nproperty float ny\nproperty float nz\nelement face 1\nproperty list uchar uint vertex_index\n\
+            element edge 1\nproperty int vertex1\nproperty int vertex2\nend_header\n\
+            0 0 0 0 0 2\n1 0 0 0 0 2\n0 1 0 0 0 2\n3 0 1 2\n0 1\n";
+        let data = parse(text.as_bytes()).unwrap();
+        assert_eq!(vec![[0, 1, 2]], data.triangles);
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), data.normals[2]);
+    }
+
+    #[test]
+    fn truncated_and_malformed_files_are_rejected() {
+        let mut bytes = binary("binary_little_endian 1.0", false);
+        bytes.truncate(bytes.len() - 3);
+        assert_eq!("unexpected end of file in face 0", parse(&bytes).err().unwrap());
+
+        let text = HEADER.replace("{}", "ascii 1.0") + "0 0 0 255 0 0\n1 0 0 255 0 51\n";
+        assert_eq!("unexpected end of file in vertex 2", parse(text.as_bytes()).err().unwrap());
+
+        assert_eq!("not a PLY file", parse(b"solid cube\nend_header\n").err().unwrap());
+        assert_eq!("missing `end_header`", parse(b"ply\nformat ascii 1.0\n").err().unwrap());
+        let bad_type = "ply\nformat ascii 1.0\nelement vertex 1\nproperty half x\nend_header\n";
+        assert_eq!("unknown property type `half`", parse(bad_type.as_bytes()).err().unwrap());
+    }
+}
@@ -1,38 +1,39 @@
+use crate::float::Float;
 use crate::vec3::Vec3;
 use std::ops::{Index, IndexMut, Add, Sub, AddAssign, SubAssign};
 use std::slice::{Iter, IterMut};
 
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 pub(crate) struct Point3(pub Vec3);
 
 impl Point3 {
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
         Point3(Vec3::new(x, y, z))
     }
 
-    pub fn x(&self) -> f32 {
+    pub fn x(&self) -> Float {
         self[0]
     }
 
-    pub fn y(&self) -> f32 {
+    pub fn y(&self) -> Float {
         self[1]
     }
 
-    pub fn z(&self) -> f32 {
+    pub fn z(&self) -> Float {
         self[2]
     }
 
-    pub fn iter(&self) -> Iter<f32> {
+    pub fn iter(&self) -> Iter<'_, Float> {
         self.0.iter()
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<f32> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, Float> {
         self.0.iter_mut()
     }
 }
 
 impl Index<u8> for Point3 {
-    type Output = f32;
+    type Output = Float;
 
     fn index(&self, index: u8) -> &Self::Output {
         &self.0[index]
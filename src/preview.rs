@@ -0,0 +1,165 @@
+use crate::float::Float;
+use crate::animation::Keyframe;
+use crate::color::Color;
+use crate::film::Film;
+use crate::hittable_list::HittableList;
+use crate::settings::Settings;
+use crate::vec3::Vec3;
+use crate::SAMPLES_PER_PIXEL;
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
+use crate::float::consts::PI;
+use std::io;
+
+const SCALE: u32 = 4;
+const ROWS_PER_UPDATE: u32 = 8;
+const ORBIT_STEP: Float = PI / 36.0;
+const ZOOM_STEP: Float = 0.9;
+
+enum Action {
+    Continue,
+    Restart,
+    Quit,
+}
+
+pub(crate) fn run(settings: &Settings, world: &HittableList, view: &Keyframe) -> io::Result<()> {
+    let (width, height) = crate::image_size(settings.projection);
+    let (width, height) = (width / SCALE, height / SCALE);
+    let mut window = Window::new(
+        "Raytracing preview",
+        width as usize,
+        height as usize,
+        WindowOptions::default(),
+    )
+    .map_err(|e| io::Error::other(e.to_string()))?;
+    window.set_target_fps(60);
+    eprintln!("Arrows/WASD orbit, Q/E zoom, R restart, Esc quit");
+
+    let mut view = *view;
+    let filter_radius = settings.filter_radius.unwrap_or_else(|| settings.filter.default_radius());
+    let mut sampler = settings.sampler.build(SAMPLES_PER_PIXEL);
+
+    'restart: loop {
+        let cam = crate::camera(settings, world, &view);
+        let mut film = Film::new(width, height, settings.filter.build(filter_radius));
+
+        for pass in 0..SAMPLES_PER_PIXEL {
+            window.set_title(&format!("Raytracing preview - pass {}/{}", pass + 1, SAMPLES_PER_PIXEL));
+            for j in (0..height).rev() {
+                for i in 0..width {
+                    let ((x, y), sample) =
+                        crate::sample_pixel(world, cam.as_ref(), sampler.as_mut(), (i, j), pass, (width, height));
+                    film.add_sample(x, y, sample.color());
+                }
+
+                if j % ROWS_PER_UPDATE == 0 {
+                    present(&mut window, settings, &film, (width, height))?;
+                    match handle_keys(&window, &mut view) {
+                        Action::Continue => {}
+                        Action::Restart => continue 'restart,
+                        Action::Quit => return Ok(()),
+                    }
+                }
+            }
+        }
+
+        loop {
+            window.update();
+            match handle_keys(&window, &mut view) {
+                Action::Continue => {}
+                Action::Restart => continue 'restart,
+                Action::Quit => return Ok(()),
+            }
+        }
+    }
+}
+
+fn present(window: &mut Window, settings: &Settings, film: &Film, (width, height): (u32, u32)) -> io::Result<()> {
+    let buffer: Vec<u32> = film
+        .resolve()
+        .into_iter()
+        .map(|pixel| {
+            let Color(c) = settings.post_process.apply(pixel);
+            let channel = |v: Float| (255.0 * v).clamp(0.0, 255.0) as u32;
+            channel(c.x()) << 16 | channel(c.y()) << 8 | channel(c.z())
+        })
+        .collect();
+
+    window
+        .update_with_buffer(&buffer, width as usize, height as usize)
+        .map_err(|e| io::Error::other(e.to_string()))
+}
+
+fn handle_keys(window: &Window, view: &mut Keyframe) -> Action {
+    if !window.is_open() || window.is_key_down(Key::Escape) {
+        return Action::Quit;
+    }
+
+    let mut action = Action::Continue;
+    for key in window.get_keys_pressed(KeyRepeat::Yes) {
+        *view = match key {
+            Key::Left | Key::A => orbit(view, -ORBIT_STEP, 0.0, 1.0),
+            Key::Right | Key::D => orbit(view, ORBIT_STEP, 0.0, 1.0),
+            Key::Up | Key::W => orbit(view, 0.0, ORBIT_STEP, 1.0),
+            Key::Down | Key::S => orbit(view, 0.0, -ORBIT_STEP, 1.0),
+            Key::Q => orbit(view, 0.0, 0.0, ZOOM_STEP),
+            Key::E => orbit(view, 0.0, 0.0, 1.0 / ZOOM_STEP),
+            Key::R => *view,
+            _ => continue,
+        };
+        action = Action::Restart;
+    }
+    action
+}
+
+fn orbit(view: &Keyframe, yaw: Float, pitch: Float, zoom: Float) -> Keyframe {
+    let offset = Vec3::from(view.origin) - Vec3::from(view.destination);
+    let distance = offset.length() * zoom;
+    let azimuth = offset.z().atan2(offset.x()) + yaw;
+    let max_elevation = PI / 2.0 - 0.01;
+    let elevation = ((offset.y() / offset.length()).asin() + pitch).clamp(-max_elevation, max_elevation);
+
+    let rotated = distance * Vec3::new(
+        elevation.cos() * azimuth.cos(),
+        elevation.sin(),
+        elevation.cos() * azimuth.sin(),
+    );
+    Keyframe {
+        origin: view.destination + rotated,
+        focus_dist: view.focus_dist * zoom,
+        ..*view
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::angle::Degrees;
+    use crate::point3::Point3;
+
+    fn view() -> Keyframe {
+        Keyframe {
+            time: 0.0,
+            origin: Point3::new(4.0, 0.0, 0.0),
+            destination: Point3::new(1.0, 0.0, 0.0),
+            fov: Degrees(20.0),
+            focus_dist: 3.0,
+        }
+    }
+
+    #[test]
+    fn orbit_keeps_distance_to_target() {
+        let key = orbit(&view(), PI / 2.0, 0.0, 1.0);
+        assert!((key.origin.x() - 1.0).abs() < 1e-5);
+        assert!((key.origin.z() - 3.0).abs() < 1e-5);
+
+        let key = orbit(&view(), 0.0, PI, 1.0);
+        assert!(key.origin.y() < 3.0 && key.origin.y() > 2.99);
+    }
+
+    #[test]
+    fn zoom_moves_focus_with_camera() {
+        let key = orbit(&view(), 0.0, 0.0, 0.5);
+        assert!((key.origin.x() - 2.5).abs() < 1e-5);
+        assert_eq!(1.5, key.focus_dist);
+    }
+}
@@ -1,17 +1,170 @@
+use crate::stats;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const BAR_INTERVAL: Duration = Duration::from_millis(100);
+const LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum ProgressMode {
+    #[default]
+    Auto,
+    Bar,
+    Log,
+    Quiet,
+}
+
+impl FromStr for ProgressMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ProgressMode::Auto),
+            "bar" => Ok(ProgressMode::Bar),
+            "log" => Ok(ProgressMode::Log),
+            "quiet" => Ok(ProgressMode::Quiet),
+            _ => Err(format!("unknown progress mode `{}`, expected one of: auto, bar, log, quiet", s)),
+        }
+    }
+}
+
+impl ProgressMode {
+    fn resolve(self) -> Self {
+        match self {
+            ProgressMode::Auto if io::stderr().is_terminal() => ProgressMode::Bar,
+            ProgressMode::Auto => ProgressMode::Log,
+            mode => mode,
+        }
+    }
+}
+
 pub(crate) struct ProgressBar {
     length: u32,
+    total: u64,
+    mode: ProgressMode,
+    done: AtomicU64,
+    start: Instant,
+    start_rays: u64,
+    last_report: Mutex<Instant>,
 }
 
 impl ProgressBar {
-    pub fn new(length: u32) -> Self {
-        ProgressBar { length }
+    pub fn new(length: u32, total: u64, mode: ProgressMode) -> Self {
+        let start = Instant::now();
+        ProgressBar {
+            length,
+            total: total.max(1),
+            mode: mode.resolve(),
+            done: AtomicU64::new(0),
+            start,
+            start_rays: stats::rays(),
+            last_report: Mutex::new(start),
+        }
+    }
+
+    pub fn advance(&self, amount: u64) {
+        let done = self.done.fetch_add(amount, Ordering::Relaxed) + amount;
+        let interval = match self.mode {
+            ProgressMode::Log => LOG_INTERVAL,
+            _ => BAR_INTERVAL,
+        };
+
+        if let Ok(mut last_report) = self.last_report.try_lock() {
+            let now = Instant::now();
+            if now.duration_since(*last_report) >= interval {
+                *last_report = now;
+                self.report(done);
+            }
+        }
+    }
+
+    pub fn finish(&self) {
+        self.report(self.done.load(Ordering::Relaxed));
+    }
+
+    fn report(&self, done: u64) {
+        let fraction = (done as f64 / self.total as f64).min(1.0);
+        let elapsed = self.start.elapsed();
+        let rays = stats::rays() - self.start_rays;
+
+        match self.mode {
+            ProgressMode::Bar => {
+                let progressed = (self.length as f64 * fraction) as u32;
+                let bar: String = (0..self.length).map(|i| if i < progressed { '#' } else { '-' }).collect();
+                eprint!("\r|{}| {}\x1b[K", bar, status(fraction, elapsed, rays));
+            }
+            ProgressMode::Log => eprintln!("progress {}", status(fraction, elapsed, rays)),
+            ProgressMode::Auto | ProgressMode::Quiet => {}
+        }
+    }
+}
+
+fn status(fraction: f64, elapsed: Duration, rays: u64) -> String {
+    let seconds = elapsed.as_secs_f64();
+    let eta = if fraction > 0.0 {
+        format_duration(Duration::from_secs_f64(seconds * (1.0 - fraction) / fraction))
+    } else {
+        "--:--".to_string()
+    };
+    let rays_per_second = if seconds > 0.0 { rays as f64 / seconds } else { 0.0 };
+
+    format!(
+        "{:5.1}% elapsed {} eta {} {} rays/s",
+        100.0 * fraction,
+        format_duration(elapsed),
+        eta,
+        format_count(rays_per_second),
+    )
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds / 3600 {
+        0 => format!("{:02}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60),
+    }
+}
+
+fn format_count(count: f64) -> String {
+    match count {
+        c if c >= 1e9 => format!("{:.2}G", c / 1e9),
+        c if c >= 1e6 => format!("{:.2}M", c / 1e6),
+        c if c >= 1e3 => format!("{:.2}k", c / 1e3),
+        c => format!("{:.0}", c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!("00:07", format_duration(Duration::from_secs(7)));
+        assert_eq!("02:05", format_duration(Duration::from_secs(125)));
+        assert_eq!("1:01:01", format_duration(Duration::from_secs(3661)));
+    }
+
+    #[test]
+    fn status_line() {
+        assert_eq!(
+            " 25.0% elapsed 00:10 eta 00:30 1.50M rays/s",
+            status(0.25, Duration::from_secs(10), 15_000_000),
+        );
+        assert_eq!("  0.0% elapsed 00:00 eta --:-- 0 rays/s", status(0.0, Duration::default(), 0));
     }
 
-    pub fn update(&self, done: f32) {
-        let progressed = (self.length as f32 * done) as u32;
-        eprint!("\r|");
-        (0..self.length)
-            .for_each(|i| eprint!("{}", if i < progressed { "#" } else { "-" }));
-        eprint!("|");
+    #[test]
+    fn progress_is_shared_between_threads() {
+        let progress = ProgressBar::new(10, 400, ProgressMode::Quiet);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| (0..100).for_each(|_| progress.advance(1)));
+            }
+        });
+        assert_eq!(400, progress.done.load(Ordering::Relaxed));
     }
 }
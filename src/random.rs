@@ -0,0 +1,52 @@
+use rand::rngs::SmallRng;
+use rand::{RngCore, SeedableRng, Error};
+use std::cell::RefCell;
+
+thread_local! {
+    static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::from_entropy());
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct SeededRng;
+
+pub(crate) fn rng() -> SeededRng {
+    SeededRng
+}
+
+pub(crate) fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = SmallRng::seed_from_u64(seed));
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::float::Float;
+    use rand::Rng;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        seed(42);
+        let a: Vec<Float> = (0..8).map(|_| rng().gen()).collect();
+        seed(42);
+        let b: Vec<Float> = (0..8).map(|_| rng().gen()).collect();
+        assert_eq!(a, b);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::float::Float;
 use crate::vec3::Vec3;
 use crate::point3::Point3;
 
@@ -12,7 +13,7 @@ impl Ray {
         Ray { origin, direction }
     }
 
-    pub fn at(&self, t: f32) -> Point3 {
+    pub fn at(&self, t: Float) -> Point3 {
         self.origin + t * self.direction
     }
 }
\ No newline at end of file
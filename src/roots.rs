@@ -0,0 +1,116 @@
+use crate::float::Float;
+
+pub(crate) fn solve_quadratic(a: Float, b: Float, c: Float) -> Option<(Float, Float)> {
+    if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 {
+            return None;
+        }
+        let t = -c / b;
+        return Some((t, t));
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let (t0, t1) = if q == 0.0 { (0.0, 0.0) } else { (q / a, c / q) };
+    Some((t0.min(t1), t0.max(t1)))
+}
+
+fn solve_cubic_real(b: f64, c: f64, d: f64) -> f64 {
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+
+    let root = if discriminant >= 0.0 {
+        let s = discriminant.sqrt();
+        (-q / 2.0 + s).cbrt() + (-q / 2.0 - s).cbrt()
+    } else {
+        let r = (-p / 3.0).sqrt();
+        let phi = (-q / (2.0 * r * r * r)).clamp(-1.0, 1.0).acos();
+        2.0 * r * (phi / 3.0).cos()
+    };
+    root - b / 3.0
+}
+
+/// Real roots of `x^4 + b x^3 + c x^2 + d x + e` via Ferrari's method, each polished with Newton steps.
+pub(crate) fn solve_quartic(b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b * b * b / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b * b * b * b / 256.0;
+
+    let mut depressed = Vec::with_capacity(4);
+    if q.abs() < 1e-12 {
+        let z = -0.25 * b;
+        for y2 in quadratic_f64(1.0, p, r) {
+            if y2 >= 0.0 {
+                depressed.push(y2.sqrt());
+                depressed.push(-y2.sqrt());
+            }
+        }
+        return polish(depressed.into_iter().map(|y| y + z).collect(), b, c, d, e);
+    }
+
+    let m = solve_cubic_real(p, p * p / 4.0 - r, -q * q / 8.0).max(1e-12);
+    let s = (2.0 * m).sqrt();
+    depressed.extend(quadratic_f64(1.0, s, p / 2.0 + m - q / (2.0 * s)));
+    depressed.extend(quadratic_f64(1.0, -s, p / 2.0 + m + q / (2.0 * s)));
+
+    polish(depressed.into_iter().map(|y| y - 0.25 * b).collect(), b, c, d, e)
+}
+
+fn quadratic_f64(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0.0 {
+        return vec![0.0];
+    }
+    vec![q / a, c / q]
+}
+
+fn polish(mut roots: Vec<f64>, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    for root in &mut roots {
+        for _ in 0..2 {
+            let x = *root;
+            let f = (((x + b) * x + c) * x + d) * x + e;
+            let df = ((4.0 * x + 3.0 * b) * x + 2.0 * c) * x + d;
+            if df.abs() > 1e-12 {
+                *root = x - f / df;
+            }
+        }
+    }
+    roots.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quadratic_roots_are_sorted() {
+        assert_eq!(Some((-3.0, 2.0)), solve_quadratic(1.0, 1.0, -6.0));
+        assert_eq!(Some((2.0, 2.0)), solve_quadratic(0.0, 2.0, -4.0));
+        assert_eq!(None, solve_quadratic(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn quartic_roots() {
+        // (x - 1)(x - 2)(x + 3)(x - 5) = x^4 - 5x^3 - 7x^2 + 41x - 30
+        let roots = solve_quartic(-5.0, -7.0, 41.0, -30.0);
+        assert_eq!(4, roots.len());
+        for (root, expected) in roots.iter().zip(&[-3.0, 1.0, 2.0, 5.0]) {
+            assert!((root - expected).abs() < 1e-9);
+        }
+
+        // (x^2 + 1)(x - 2)(x - 4) has only two real roots
+        let roots = solve_quartic(-6.0, 9.0, -6.0, 8.0);
+        assert_eq!(2, roots.len());
+        assert!((roots[0] - 2.0).abs() < 1e-9 && (roots[1] - 4.0).abs() < 1e-9);
+    }
+}
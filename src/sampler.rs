@@ -0,0 +1,361 @@
+use crate::float::Float;
+use crate::random;
+use rand::Rng;
+use std::str::FromStr;
+
+const ONE_MINUS_EPSILON: Float = 1.0 - Float::EPSILON / 2.0;
+/// Bases of the Halton dimensions, as many as pbrt uses; later dimensions fall back to hashed uniform values.
+const PRIMES: [u32; 1000] = first_primes();
+
+const fn first_primes<const N: usize>() -> [u32; N] {
+    let mut primes = [0; N];
+    let mut count = 0;
+    let mut candidate = 2;
+    while count < N {
+        let mut i = 0;
+        while i < count && primes[i] * primes[i] <= candidate && candidate % primes[i] != 0 {
+            i += 1;
+        }
+        if i == count || primes[i] * primes[i] > candidate {
+            primes[count] = candidate;
+            count += 1;
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+pub(crate) trait Sampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32);
+    fn get_1d(&mut self) -> Float;
+    fn get_2d(&mut self) -> (Float, Float);
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum SamplerKind {
+    Uniform,
+    Stratified,
+    Halton,
+    #[default]
+    Sobol,
+}
+
+impl FromStr for SamplerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(SamplerKind::Uniform),
+            "stratified" => Ok(SamplerKind::Stratified),
+            "halton" => Ok(SamplerKind::Halton),
+            "sobol" => Ok(SamplerKind::Sobol),
+            _ => Err(format!("unknown sampler `{}`, expected one of: uniform, stratified, halton, sobol", s)),
+        }
+    }
+}
+
+impl SamplerKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            SamplerKind::Uniform => "uniform",
+            SamplerKind::Stratified => "stratified",
+            SamplerKind::Halton => "halton",
+            SamplerKind::Sobol => "sobol",
+        }
+    }
+
+    pub fn build(self, samples_per_pixel: u32) -> Box<dyn Sampler> {
+        let seed = random::rng().gen();
+        match self {
+            SamplerKind::Uniform => Box::new(UniformSampler),
+            SamplerKind::Stratified => Box::new(StratifiedSampler::new(samples_per_pixel, seed)),
+            SamplerKind::Halton => Box::new(HaltonSampler::new(seed)),
+            SamplerKind::Sobol => Box::new(SobolSampler::new(seed)),
+        }
+    }
+}
+
+pub(crate) struct UniformSampler;
+
+impl Sampler for UniformSampler {
+    fn start_pixel_sample(&mut self, _pixel: (u32, u32), _index: u32) {}
+
+    fn get_1d(&mut self) -> Float {
+        random::rng().gen()
+    }
+
+    fn get_2d(&mut self) -> (Float, Float) {
+        let mut rng = random::rng();
+        (rng.gen(), rng.gen())
+    }
+}
+
+#[derive(Default)]
+struct SampleState {
+    pixel_seed: u64,
+    index: u32,
+    dimension: u32,
+}
+
+impl SampleState {
+    fn start(&mut self, seed: u64, pixel: (u32, u32), index: u32) {
+        self.pixel_seed = mix_bits(seed ^ ((pixel.0 as u64) << 32 | pixel.1 as u64));
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_dimension(&mut self) -> (u32, u32) {
+        let hash = mix_bits(self.pixel_seed ^ self.dimension as u64);
+        let dimension = self.dimension;
+        self.dimension += 1;
+        (dimension, hash as u32)
+    }
+}
+
+pub(crate) struct StratifiedSampler {
+    strata_x: u32,
+    strata_y: u32,
+    samples_per_pixel: u32,
+    seed: u64,
+    state: SampleState,
+}
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Self {
+        let strata_x = (samples_per_pixel as Float).sqrt().ceil() as u32;
+        let strata_y = samples_per_pixel.div_ceil(strata_x);
+        StratifiedSampler {
+            strata_x,
+            strata_y,
+            samples_per_pixel,
+            seed,
+            state: SampleState::default(),
+        }
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.state.start(self.seed, pixel, index);
+    }
+
+    fn get_1d(&mut self) -> Float {
+        let (_, hash) = self.state.next_dimension();
+        let strata = self.samples_per_pixel;
+        let stratum = permutation_element(self.state.index % strata, strata, hash);
+        let jitter: Float = random::rng().gen();
+        Float::min((stratum as Float + jitter) / strata as Float, ONE_MINUS_EPSILON)
+    }
+
+    fn get_2d(&mut self) -> (Float, Float) {
+        let (_, hash) = self.state.next_dimension();
+        let strata = self.strata_x * self.strata_y;
+        let stratum = permutation_element(self.state.index % strata, strata, hash);
+        let mut rng = random::rng();
+        let (jx, jy): (Float, Float) = (rng.gen(), rng.gen());
+        (
+            Float::min(((stratum % self.strata_x) as Float + jx) / self.strata_x as Float, ONE_MINUS_EPSILON),
+            Float::min(((stratum / self.strata_x) as Float + jy) / self.strata_y as Float, ONE_MINUS_EPSILON),
+        )
+    }
+}
+
+pub(crate) struct HaltonSampler {
+    seed: u64,
+    state: SampleState,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        HaltonSampler { seed, state: SampleState::default() }
+    }
+
+    fn sample_dimension(&mut self) -> Float {
+        let (dimension, hash) = self.state.next_dimension();
+        let base = match PRIMES.get(dimension as usize) {
+            Some(base) => *base,
+            None => return to_unit_float(mix_bits((hash as u64) << 32 | self.state.index as u64) as u32),
+        };
+        let shift = hash as Float / 4_294_967_296.0;
+        let value = radical_inverse(base, self.state.index) + shift;
+        Float::min(value - value.floor(), ONE_MINUS_EPSILON)
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.state.start(self.seed, pixel, index);
+    }
+
+    fn get_1d(&mut self) -> Float {
+        self.sample_dimension()
+    }
+
+    fn get_2d(&mut self) -> (Float, Float) {
+        (self.sample_dimension(), self.sample_dimension())
+    }
+}
+
+pub(crate) struct SobolSampler {
+    seed: u64,
+    state: SampleState,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        SobolSampler { seed, state: SampleState::default() }
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.state.start(self.seed, pixel, index);
+    }
+
+    fn get_1d(&mut self) -> Float {
+        let (_, hash) = self.state.next_dimension();
+        let index = nested_uniform_scramble(self.state.index, hash);
+        to_unit_float(nested_uniform_scramble(index.reverse_bits(), mix_bits(hash as u64 + 1) as u32))
+    }
+
+    fn get_2d(&mut self) -> (Float, Float) {
+        let (_, hash) = self.state.next_dimension();
+        let index = nested_uniform_scramble(self.state.index, hash);
+        (
+            to_unit_float(nested_uniform_scramble(index.reverse_bits(), mix_bits(hash as u64 + 1) as u32)),
+            to_unit_float(nested_uniform_scramble(sobol_second_dimension(index), mix_bits(hash as u64 + 2) as u32)),
+        )
+    }
+}
+
+fn to_unit_float(bits: u32) -> Float {
+    Float::min(bits as Float / 4_294_967_296.0, ONE_MINUS_EPSILON)
+}
+
+fn radical_inverse(base: u32, mut index: u32) -> Float {
+    let inv_base = 1.0 / base as f64;
+    let mut inv_base_n = 1.0;
+    let mut reversed = 0.0;
+    while index > 0 {
+        let next = index / base;
+        let digit = index - next * base;
+        reversed = reversed * base as f64 + digit as f64;
+        inv_base_n *= inv_base;
+        index = next;
+    }
+    Float::min((reversed * inv_base_n) as Float, ONE_MINUS_EPSILON)
+}
+
+fn sobol_second_dimension(mut index: u32) -> u32 {
+    let mut direction = 1u32 << 31;
+    let mut result = 0;
+    while index != 0 {
+        if index & 1 == 1 {
+            result ^= direction;
+        }
+        direction ^= direction >> 1;
+        index >>= 1;
+    }
+    result
+}
+
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    let mut x = x.reverse_bits().wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+    x.reverse_bits()
+}
+
+fn permutation_element(mut i: u32, length: u32, seed: u32) -> u32 {
+    let mut mask = length - 1;
+    mask |= mask >> 1;
+    mask |= mask >> 2;
+    mask |= mask >> 4;
+    mask |= mask >> 8;
+    mask |= mask >> 16;
+
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= seed >> 16;
+        i ^= (i & mask) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= seed >> 23;
+        i ^= (i & mask) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & mask) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & mask) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & mask) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= mask;
+        i ^= i >> 5;
+        if i < length {
+            break;
+        }
+    }
+
+    (i.wrapping_add(seed)) % length
+}
+
+fn mix_bits(mut v: u64) -> u64 {
+    v ^= v >> 31;
+    v = v.wrapping_mul(0x7fb5_d329_728e_a185);
+    v ^= v >> 27;
+    v = v.wrapping_mul(0x81da_def4_bc2d_d44d);
+    v ^= v >> 33;
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quadrants(sampler: &mut dyn Sampler, samples: u32) -> Vec<u32> {
+        let mut counts = vec![0; 4];
+        for i in 0..samples {
+            sampler.start_pixel_sample((3, 7), i);
+            let (x, y) = sampler.get_2d();
+            assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+            counts[(x >= 0.5) as usize + 2 * (y >= 0.5) as usize] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn radical_inverse_base2() {
+        assert_eq!(0.5, radical_inverse(2, 1));
+        assert_eq!(0.25, radical_inverse(2, 2));
+        assert_eq!(0.75, radical_inverse(2, 3));
+    }
+
+    #[test]
+    fn permutation_is_bijective() {
+        let mut elements: Vec<u32> = (0..10).map(|i| permutation_element(i, 10, 1234)).collect();
+        elements.sort();
+        assert_eq!((0..10).collect::<Vec<_>>(), elements);
+    }
+
+    #[test]
+    fn low_discrepancy_samplers_are_stratified() {
+        assert_eq!(vec![4, 4, 4, 4], quadrants(&mut StratifiedSampler::new(16, 1), 16));
+        assert_eq!(vec![4, 4, 4, 4], quadrants(&mut SobolSampler::new(1), 16));
+    }
+
+    #[test]
+    fn halton_samples_in_unit_square() {
+        let counts = quadrants(&mut HaltonSampler::new(1), 64);
+        assert_eq!(64, counts.iter().sum::<u32>());
+    }
+
+    #[test]
+    fn prime_table() {
+        assert_eq!([2, 3, 5, 7, 11, 13], PRIMES[..6]);
+        assert_eq!(7919, PRIMES[999]);
+    }
+}
@@ -0,0 +1,437 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::vec3::Vec3;
+use crate::point3::Point3;
+use crate::hittable_list::HittableList;
+use crate::sphere::Sphere;
+use crate::plane::Plane;
+use crate::disk::Disk;
+use crate::cylinder::Cylinder;
+use crate::cone::Cone;
+use crate::torus::Torus;
+use crate::cuboid::Cuboid;
+use crate::csg::{Csg, CsgOp};
+use crate::sdf::{Sdf, SdfObject};
+use crate::aabb::Aabb;
+use crate::heightfield::Heightfield;
+use crate::perlin::Perlin;
+use crate::animation::Keyframe;
+use crate::mesh::{Mesh, MeshData};
+use crate::angle::Degrees;
+use crate::hit::Hit;
+use crate::{gltf_scene, ply, scene_file, stl};
+use crate::instance::{Instance, Tlas};
+use crate::transform::Transform;
+use crate::float::consts::PI;
+use crate::material::{Lambertian, Metal, Material};
+use crate::material_library::MaterialLibrary;
+use crate::texture::{NoiseTexture, TurbulenceTexture, MarbleTexture};
+use crate::random;
+use rand::Rng;
+use std::rc::Rc;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum SceneKind {
+    #[default]
+    RandomSpheres,
+    PerlinSpheres,
+    Shapes,
+    Csg,
+    Sdf,
+    Terrain,
+    Instances,
+}
+
+impl FromStr for SceneKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(SceneKind::RandomSpheres),
+            "perlin" => Ok(SceneKind::PerlinSpheres),
+            "shapes" => Ok(SceneKind::Shapes),
+            "csg" => Ok(SceneKind::Csg),
+            "sdf" => Ok(SceneKind::Sdf),
+            "terrain" => Ok(SceneKind::Terrain),
+            "instances" => Ok(SceneKind::Instances),
+            _ => Err(format!("unknown scene `{}`, expected one of: random, perlin, shapes, csg, sdf, terrain, instances", s)),
+        }
+    }
+}
+
+pub(crate) struct LoadedScene {
+    pub world: HittableList,
+    pub view: Option<Keyframe>,
+    /// Named materials, used to refer to them by name when the scene is exported.
+    pub materials: MaterialLibrary,
+    /// Camera and sampling options saved with the scene, as command line arguments.
+    pub options: Vec<String>,
+}
+
+pub(crate) fn load(path: &str) -> Result<LoadedScene, String> {
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("gltf") | Some("glb") => gltf_scene::import(path),
+        Some("ply") => framed_mesh(ply::read(path)?),
+        Some("stl") => framed_mesh(stl::read(path)?),
+        Some("scene") => scene_file::read(path),
+        _ => Err(format!("unknown scene file `{}`, expected one of: .scene, .gltf, .glb, .ply, .stl", path)),
+    }
+}
+
+/// A lone scanned or CAD mesh, with a view that fits its bounding sphere.
+fn framed_mesh(data: MeshData) -> Result<LoadedScene, String> {
+    // Vertex colors multiply the albedo, so keep it white when they're present.
+    let albedo = if data.colors.is_empty() { Color::new(0.7, 0.7, 0.7) } else { Color::new(1.0, 1.0, 1.0) };
+    let mesh = Mesh::new(data, Rc::new(Lambertian::new(albedo)))?;
+    let bounds = mesh.bounding_box().ok_or_else(|| "mesh has no triangles".to_string())?;
+
+    let half_diagonal = 0.5 * (Vec3::from(bounds.max) - Vec3::from(bounds.min));
+    let center = bounds.min + half_diagonal;
+    let fov = Degrees(40.0);
+    let distance = half_diagonal.length() / (0.5 * fov.0.to_radians()).sin();
+    let origin = center + distance * Vec3::new(1.0, 0.6, 1.6).unit_vec();
+
+    let mut world = HittableList::default();
+    world.add(Rc::new(mesh));
+    let view = Keyframe { time: 0.0, origin, destination: center, fov, focus_dist: distance };
+    Ok(LoadedScene { world, view: Some(view), materials: MaterialLibrary::new(), options: Vec::new() })
+}
+
+/// `heightmap` replaces the procedural terrain heights of `SceneKind::Terrain`.
+pub(crate) fn build(kind: SceneKind, heightmap: Option<&str>) -> Result<LoadedScene, String> {
+    let materials = MaterialLibrary::new();
+    let world = match kind {
+        SceneKind::RandomSpheres => random_spheres(&materials),
+        SceneKind::PerlinSpheres => perlin_spheres(),
+        SceneKind::Shapes => shapes(&materials),
+        SceneKind::Csg => csg(&materials),
+        SceneKind::Sdf => sdf(),
+        SceneKind::Terrain => terrain(heightmap, &materials)?,
+        SceneKind::Instances => instances(&materials),
+    };
+    Ok(LoadedScene { world, view: None, materials, options: Vec::new() })
+}
+
+fn random_spheres(materials: &MaterialLibrary) -> HittableList {
+    let mut rng = random::rng();
+    let mut world = HittableList::default();
+
+    let ground = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Rc::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        ground,
+    )));
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let center = Point3::new(a as Float + 0.9 * rng.gen::<Float>(), 0.2, b as Float + 0.9 * rng.gen::<Float>());
+
+            if (Vec3::from(center) - Vec3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+                let sphere_material: Rc<dyn Material> = match rng.gen::<Float>() {
+                    x if (0.0..0.8).contains(&x) => {
+                        let albedo = Color::random();
+                        Rc::new(Lambertian::new(albedo))
+                    },
+                    x if (0.8..0.9).contains(&x) => {
+                        let albedo = Color::random_range(0.5, 1.0);
+                        let roughness = rng.gen_range(0.0, 0.5);
+                        Rc::new(Metal::new(albedo, roughness))
+                    },
+                    _ => materials.preset("glass"),
+                };
+
+                world.add(Rc::new(Sphere::new(
+                    center,
+                    0.2,
+                    sphere_material,
+                )));
+            }
+        }
+    }
+
+    world.add(Rc::new(Sphere::new(
+        Point3::new(-4.0, 1.0, 0.0),
+        1.0,
+        Rc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))),
+    )));
+    world.add(Rc::new(Sphere::new(
+        Point3::new(4.0, 1.0, 0.0),
+        1.0,
+        Rc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)),
+    )));
+    world.add(Rc::new(Sphere::new(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        materials.preset("glass"),
+    )));
+
+    world
+}
+
+fn perlin_spheres() -> HittableList {
+    let mut world = HittableList::default();
+
+    world.add(Rc::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Rc::new(Lambertian::textured(Rc::new(TurbulenceTexture::new(Perlin::new(), 4.0, 7)))),
+    )));
+    world.add(Rc::new(Sphere::new(
+        Point3::new(0.0, 2.0, 0.0),
+        2.0,
+        Rc::new(Lambertian::textured(Rc::new(MarbleTexture::new(Perlin::new(), 4.0, 7)))),
+    )));
+    world.add(Rc::new(Sphere::new(
+        Point3::new(0.0, 0.5, 3.0),
+        0.5,
+        Rc::new(Lambertian::textured(Rc::new(NoiseTexture::new(Perlin::new(), 8.0)))),
+    )));
+
+    world
+}
+
+fn shapes(materials: &MaterialLibrary) -> HittableList {
+    let mut world = HittableList::default();
+
+    world.add(Rc::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+    world.add(Rc::new(Cylinder::new(
+        Point3::new(0.0, 0.0, -3.0),
+        0.6,
+        1.5,
+        true,
+        Rc::new(Lambertian::new(Color::new(0.8, 0.3, 0.2))),
+    )));
+    world.add(Rc::new(Cone::new(
+        Point3::new(0.0, 0.0, -1.5),
+        0.6,
+        1.5,
+        true,
+        Rc::new(Lambertian::new(Color::new(0.2, 0.5, 0.8))),
+    )));
+    world.add(Rc::new(Torus::new(
+        Point3::new(0.0, 0.3, 0.3),
+        0.7,
+        0.3,
+        Rc::new(Metal::new(Color::new(0.9, 0.75, 0.4), 0.1)),
+    )));
+    world.add(Rc::new(Cylinder::new(
+        Point3::new(0.0, 0.0, 2.0),
+        0.5,
+        1.2,
+        false,
+        Rc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.0)),
+    )));
+    world.add(Rc::new(Disk::new(
+        Point3::new(-2.0, 1.2, 3.5),
+        Vec3::new(1.0, 0.0, 0.2),
+        1.0,
+        Rc::new(Lambertian::new(Color::new(0.3, 0.7, 0.3))),
+    )));
+    world.add(Rc::new(Sphere::new(
+        Point3::new(2.0, 0.5, -0.5),
+        0.5,
+        materials.preset("glass"),
+    )));
+
+    world
+}
+
+fn csg(materials: &MaterialLibrary) -> HittableList {
+    let mut world = HittableList::default();
+
+    world.add(Rc::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+
+    // Biconvex lens: the overlap of two large glass spheres.
+    let glass = materials.preset("glass");
+    world.add(Rc::new(Csg::new(
+        CsgOp::Intersection,
+        Rc::new(Sphere::new(Point3::new(-2.0, 1.4, 1.8), 2.0, glass.clone())),
+        Rc::new(Sphere::new(Point3::new(1.0, 1.4, 1.8), 2.0, glass)),
+    )));
+
+    // Machined part: a rounded block with a bore drilled through it.
+    let steel: Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.8, 0.8, 0.85), 0.2));
+    let block = Csg::new(
+        CsgOp::Intersection,
+        Rc::new(Cuboid::new(Point3::new(-0.8, 0.0, -0.8), Point3::new(0.8, 1.6, 0.8), steel.clone())),
+        Rc::new(Sphere::new(Point3::new(0.0, 0.8, 0.0), 1.05, steel)),
+    );
+    world.add(Rc::new(Csg::new(
+        CsgOp::Difference,
+        Rc::new(block),
+        Rc::new(Cylinder::new(
+            Point3::new(0.0, -0.1, 0.0),
+            0.45,
+            1.8,
+            true,
+            Rc::new(Lambertian::new(Color::new(0.8, 0.3, 0.2))),
+        )),
+    )));
+
+    // Sphere with a corner bitten out, fused to a pedestal.
+    let red: Rc<dyn Material> = Rc::new(Lambertian::new(Color::new(0.7, 0.2, 0.2)));
+    let bitten = Csg::new(
+        CsgOp::Difference,
+        Rc::new(Sphere::new(Point3::new(0.0, 1.3, -2.5), 0.7, red.clone())),
+        Rc::new(Cuboid::new(Point3::new(0.0, 1.3, -2.5), Point3::new(1.0, 2.3, -1.5), red.clone())),
+    );
+    world.add(Rc::new(Csg::new(
+        CsgOp::Union,
+        Rc::new(bitten),
+        Rc::new(Cylinder::new(Point3::new(0.0, 0.0, -2.5), 0.25, 0.8, true, red)),
+    )));
+
+    world
+}
+
+fn sdf() -> HittableList {
+    let mut world = HittableList::default();
+
+    world.add(Rc::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+
+    let blobs = Sdf::sphere(Point3::new(0.0, 0.7, 0.0), 0.7)
+        .smooth_union(Sdf::sphere(Point3::new(0.0, 1.5, 0.6), 0.45), 0.4)
+        .smooth_union(Sdf::sphere(Point3::new(0.0, 1.1, -0.8), 0.4), 0.4);
+    world.add(Rc::new(SdfObject::new(
+        blobs,
+        Aabb::new(Point3::new(-1.0, -0.1, -1.5), Point3::new(1.0, 2.2, 1.3)),
+        Rc::new(Lambertian::new(Color::new(0.2, 0.5, 0.8))),
+    )));
+
+    let rounded = Sdf::cuboid(Point3::new(0.0, 0.6, -2.5), Vec3::new(0.4, 0.4, 0.4)).round(0.2);
+    world.add(Rc::new(SdfObject::new(
+        rounded,
+        Aabb::new(Point3::new(-0.7, -0.1, -3.2), Point3::new(0.7, 1.3, -1.8)),
+        Rc::new(Metal::new(Color::new(0.9, 0.75, 0.4), 0.05)),
+    )));
+
+    let bumpy = Sdf::sphere(Point3::new(0.0, 0.8, 2.5), 0.7).displace(0.05, 12.0);
+    world.add(Rc::new(SdfObject::new(
+        bumpy,
+        Aabb::new(Point3::new(-0.8, -0.1, 1.6), Point3::new(0.8, 1.6, 3.4)),
+        Rc::new(Lambertian::new(Color::new(0.8, 0.3, 0.2))),
+    )));
+
+    let pebbles = Sdf::sphere(Point3::new(0.0, 0.1, 0.0), 0.12).repeat(Vec3::new(0.5, 0.0, 0.5));
+    world.add(Rc::new(SdfObject::new(
+        pebbles,
+        Aabb::new(Point3::new(1.75, 0.0, -3.25), Point3::new(4.25, 0.25, 3.25)),
+        Rc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.3)),
+    )));
+
+    world
+}
+
+fn terrain(heightmap: Option<&str>, materials: &MaterialLibrary) -> Result<HittableList, String> {
+    let mut world = HittableList::default();
+
+    let (corner, size) = (Point3::new(-12.0, -1.5, -12.0), Vec3::new(24.0, 2.5, 24.0));
+    let ground = Rc::new(Lambertian::new(Color::new(0.45, 0.5, 0.3)));
+    let field = match heightmap {
+        Some(path) => Heightfield::from_pgm(path, corner, size, ground)?,
+        None => {
+            let perlin = Perlin::new();
+            let height = |x: Float, z: Float| {
+                let p = Vec3::new(4.0 * x, 0.0, 4.0 * z);
+                let hills = perlin.noise(&p.into()) + 0.5 * perlin.noise(&(2.0 * p).into()) + 0.25 * perlin.noise(&(4.0 * p).into());
+                (0.5 + 0.4 * hills).clamp(0.0, 1.0)
+            };
+            Heightfield::from_fn(257, 257, height, corner, size, ground)
+        },
+    };
+    world.add(Rc::new(field));
+
+    world.add(Rc::new(Plane::new(
+        Point3::new(0.0, -0.6, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Rc::new(Metal::new(Color::new(0.3, 0.45, 0.6), 0.02)),
+    )));
+    world.add(Rc::new(Sphere::new(
+        Point3::new(0.0, 1.6, 0.0),
+        0.6,
+        materials.preset("glass"),
+    )));
+
+    Ok(world)
+}
+
+/// Ten thousand torus knots on a grid, sharing the triangles and BVH of four meshes.
+fn instances(materials: &MaterialLibrary) -> HittableList {
+    let mut rng = random::rng();
+    let mut world = HittableList::default();
+
+    world.add(Rc::new(Plane::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+
+    let knots: Vec<Rc<dyn Hit>> = ["gold", "silver", "copper", "plastic-red"]
+        .iter()
+        .map(|name| Rc::new(torus_knot(materials.preset(name))) as Rc<dyn Hit>)
+        .collect();
+    let mut instances = Vec::new();
+    for a in -50..50 {
+        for b in -50..50 {
+            let scale = rng.gen_range(0.25, 0.4);
+            let axis = Vec3::new(rng.gen_range(-1.0, 1.0), 1.0, rng.gen_range(-1.0, 1.0));
+            let position = Vec3::new(a as Float + rng.gen_range(0.3, 0.7), scale, b as Float + rng.gen_range(0.3, 0.7));
+            let transform = Transform::scale(Vec3::new(scale, scale, scale))
+                .then(&Transform::rotate(axis, Degrees(rng.gen_range(0.0, 360.0))))
+                .then(&Transform::translate(position));
+            instances.push(Instance::new(knots[rng.gen_range(0, knots.len())].clone(), transform));
+        }
+    }
+    world.add(Rc::new(Tlas::new(instances)));
+
+    world
+}
+
+/// Smooth tube around a (2, 3) torus knot of radius about 1.
+pub(crate) fn torus_knot(material: Rc<dyn Material>) -> Mesh {
+    let (segments, sides, tube) = (160, 12, 0.18);
+    let curve = |t: Float| {
+        let r = 0.6 + 0.3 * (3.0 * t).cos();
+        Vec3::new(r * (2.0 * t).cos(), 0.3 * (3.0 * t).sin(), r * (2.0 * t).sin())
+    };
+
+    let mut data = MeshData::default();
+    for i in 0..segments {
+        let (t, dt) = (i as Float / segments as Float * 2.0 * PI, 1e-3);
+        let center = curve(t);
+        let tangent = (curve(t + dt) - curve(t - dt)).unit_vec();
+        let normal = (curve(t + dt) + curve(t - dt) - 2.0 * center).unit_vec();
+        let binormal = tangent.cross(&normal);
+        for j in 0..sides {
+            let angle = j as Float / sides as Float * 2.0 * PI;
+            let direction = angle.cos() * normal + angle.sin() * binormal;
+            data.positions.push((center + tube * direction).into());
+            data.normals.push(direction);
+        }
+    }
+    for i in 0..segments {
+        for j in 0..sides {
+            let vertex = |i: usize, j: usize| (i % segments) * sides + j % sides;
+            let (a, b, c, d) = (vertex(i, j), vertex(i + 1, j), vertex(i + 1, j + 1), vertex(i, j + 1));
+            data.triangles.push([a, b, c]);
+            data.triangles.push([a, c, d]);
+        }
+    }
+    Mesh::new(data, material).expect("torus knot indices")
+}
@@ -0,0 +1,445 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::point3::Point3;
+use crate::vec3::Vec3;
+use crate::angle::Degrees;
+use crate::animation::Keyframe;
+use crate::camera::Projection;
+use crate::sampler::SamplerKind;
+use crate::filter::FilterKind;
+use crate::settings::{Pixel, Settings};
+use crate::hit::Hit;
+use crate::hittable_list::HittableList;
+use crate::scene::LoadedScene;
+use crate::sphere::Sphere;
+use crate::plane::Plane;
+use crate::disk::Disk;
+use crate::cylinder::Cylinder;
+use crate::cone::Cone;
+use crate::torus::Torus;
+use crate::cuboid::Cuboid;
+use crate::material::{Lambertian, Metal, Plastic, Dielectric, Material};
+use crate::texture::{NoiseTexture, TurbulenceTexture, MarbleTexture};
+use crate::perlin::Perlin;
+use crate::material_library::MaterialLibrary;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// Collects the statements of a scene file, declaring each shared material once under its library name,
+/// or a generated one for materials outside the library.
+pub(crate) struct SceneWriter {
+    lines: Vec<String>,
+    names: HashMap<*const (), String>,
+    declared: HashSet<*const ()>,
+    generated: usize,
+    skipped: usize,
+}
+
+impl SceneWriter {
+    fn new(library: &MaterialLibrary) -> Self {
+        SceneWriter {
+            lines: Vec::new(),
+            names: library.iter().map(|(name, material)| (Rc::as_ptr(material) as *const (), name.to_string())).collect(),
+            declared: HashSet::new(),
+            generated: 0,
+            skipped: 0,
+        }
+    }
+
+    pub fn add(&mut self, object: &dyn Hit) {
+        if object.export(self).is_none() {
+            self.skipped += 1;
+        }
+    }
+
+    pub fn entry(&mut self, line: String) {
+        self.lines.push(line);
+    }
+
+    /// Name of `material`, declaring it first if this is its first use.
+    pub fn material(&mut self, material: &Rc<dyn Material>) -> Option<String> {
+        let key = Rc::as_ptr(material) as *const ();
+        if self.declared.contains(&key) {
+            return self.names.get(&key).cloned();
+        }
+        let description = material.export()?;
+        if !self.names.contains_key(&key) {
+            let taken: HashSet<&String> = self.names.values().collect();
+            let name = (self.generated..).map(|n| format!("m{}", n)).find(|name| !taken.contains(name))?;
+            self.generated += 1;
+            self.names.insert(key, name);
+        }
+        let name = self.names[&key].clone();
+        self.lines.push(format!("material {} {}", name, description));
+        self.declared.insert(key);
+        Some(name)
+    }
+
+    fn finish(self, view: &Keyframe, settings: &Settings) -> (String, usize) {
+        let mut text = String::from("# raytracing scene\n");
+        text += &format!("camera {} {} {} {}\n", point(view.origin), point(view.destination), view.fov.0, view.focus_dist);
+        for line in camera_statements(settings).into_iter().chain(self.lines) {
+            text += &line;
+            text.push('\n');
+        }
+        (text, self.skipped)
+    }
+}
+
+/// How the camera projects and samples the view, in statements that load back into the same options.
+fn camera_statements(settings: &Settings) -> Vec<String> {
+    let mut lines = vec![format!("projection {}", settings.projection.name())];
+    if let Some(lens) = &settings.lens {
+        lines.push(format!("lens {} {} {} {}", lens.focal_length, lens.f_stop, lens.sensor_width, lens.sensor_height));
+    }
+    if let Some(path) = &settings.aperture_mask {
+        lines.push(format!("aperture mask {}", path));
+    } else if let Some(blades) = settings.aperture_blades {
+        lines.push(format!("aperture blades {}", blades));
+    }
+    if let Some(pixel) = settings.autofocus {
+        lines.push(format!("autofocus {} {}", pixel.x, pixel.y));
+    }
+    lines.push(format!("sampler {}", settings.sampler.name()));
+    lines.push(match settings.filter_radius {
+        Some(radius) => format!("filter {} {}", settings.filter.name(), radius),
+        None => format!("filter {}", settings.filter.name()),
+    });
+    lines
+}
+
+pub(crate) fn point(p: Point3) -> String {
+    format!("{} {} {}", p.x(), p.y(), p.z())
+}
+
+pub(crate) fn vector(v: Vec3) -> String {
+    format!("{} {} {}", v.x(), v.y(), v.z())
+}
+
+pub(crate) fn color(c: Color) -> String {
+    format!("{} {} {}", c.r(), c.g(), c.b())
+}
+
+pub(crate) fn write(
+    path: &str,
+    world: &HittableList,
+    materials: &MaterialLibrary,
+    view: &Keyframe,
+    settings: &Settings,
+) -> Result<(), String> {
+    let (text, skipped) = export(world, materials, view, settings);
+    if skipped > 0 {
+        eprintln!("Skipped {} objects the scene format can't describe", skipped);
+    }
+    fs::write(path, text).map_err(|e| format!("can't write `{}`: {}", path, e))
+}
+
+fn export(world: &HittableList, materials: &MaterialLibrary, view: &Keyframe, settings: &Settings) -> (String, usize) {
+    let mut writer = SceneWriter::new(materials);
+    writer.add(world);
+    writer.finish(view, settings)
+}
+
+pub(crate) fn read(path: &str) -> Result<LoadedScene, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("can't read `{}`: {}", path, e))?;
+    parse(&contents).map_err(|e| format!("{}:{}", path, e))
+}
+
+fn parse(text: &str) -> Result<LoadedScene, String> {
+    let mut materials = MaterialLibrary::new();
+    let mut world = HittableList::default();
+    let mut view = None;
+    let mut options = Vec::new();
+
+    let lines = text.lines().enumerate().map(|(n, line)| (n + 1, line.split('#').next().unwrap_or("").trim()));
+    for (n, line) in lines.filter(|(_, line)| !line.is_empty()) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let statement = match words.as_slice() {
+            ["camera", values @ ..] => numbers(values, 8, "camera ox oy oz dx dy dz fov focus_dist").map(|v| {
+                view = Some(Keyframe {
+                    time: 0.0,
+                    origin: Point3::new(v[0], v[1], v[2]),
+                    destination: Point3::new(v[3], v[4], v[5]),
+                    fov: Degrees(v[6]),
+                    focus_dist: v[7],
+                });
+            }),
+            ["projection", name] => option::<Projection>(&mut options, "--projection", name),
+            ["sampler", name] => option::<SamplerKind>(&mut options, "--sampler", name),
+            ["filter", name, radius @ ..] => option::<FilterKind>(&mut options, "--filter", name).and_then(|_| {
+                if !radius.is_empty() {
+                    let v = numbers(radius, 1, "filter name [radius]")?;
+                    options.extend(["--filter-radius".to_string(), v[0].to_string()]);
+                }
+                Ok(())
+            }),
+            ["lens", values @ ..] => numbers(values, 4, "lens focal_length f_stop sensor_width sensor_height").map(|v| {
+                options.extend(["--focal-length".to_string(), v[0].to_string(), "--f-stop".to_string(), v[1].to_string()]);
+                options.extend(["--sensor".to_string(), format!("{}x{}", v[2], v[3])]);
+            }),
+            ["aperture", "blades", blades] => option::<u32>(&mut options, "--aperture-blades", blades),
+            ["aperture", "mask", path @ ..] if !path.is_empty() => {
+                options.extend(["--aperture-mask".to_string(), path.join(" ")]);
+                Ok(())
+            },
+            ["autofocus", x, y] => option::<Pixel>(&mut options, "--autofocus", &format!("{},{}", x, y)),
+            ["material", name, description @ ..] => parse_material(description).map(|material| {
+                materials.insert(name, material);
+            }),
+            [kind, args @ ..] => parse_object(kind, args, &materials).map(|object| world.add(object)),
+            [] => Ok(()),
+        };
+        statement.map_err(|e| format!("{}: {}", n, e))?;
+    }
+
+    Ok(LoadedScene { world, view, materials, options })
+}
+
+/// Checks `value` parses the way `flag` expects and adds both to `options`.
+fn option<T: FromStr>(options: &mut Vec<String>, flag: &str, value: &str) -> Result<(), String>
+    where T::Err: ToString
+{
+    value.parse::<T>().map_err(|e| format!("invalid value for `{}`: {}", flag, e.to_string()))?;
+    options.extend([flag.to_string(), value.to_string()]);
+    Ok(())
+}
+
+fn numbers(values: &[&str], count: usize, usage: &str) -> Result<Vec<Float>, String> {
+    if values.len() != count {
+        return Err(format!("expected `{}`", usage));
+    }
+    values.iter().map(|v| v.parse().map_err(|_| format!("invalid number `{}`", v))).collect()
+}
+
+/// Texture parameters followed by an optional noise seed. Without one, the noise differs on every load.
+fn noise_parameters(values: &[&str], count: usize, usage: &str) -> Result<(Vec<Float>, Perlin), String> {
+    match values.split_at_checked(count) {
+        Some((parameters, [seed])) => {
+            let seed = seed.parse().map_err(|_| format!("invalid seed `{}`", seed))?;
+            Ok((numbers(parameters, count, usage)?, Perlin::with_seed(seed)))
+        }
+        _ => Ok((numbers(values, count, usage)?, Perlin::new())),
+    }
+}
+
+fn parse_material(description: &[&str]) -> Result<Rc<dyn Material>, String> {
+    Ok(match description {
+        ["lambertian", "noise", args @ ..] => {
+            let (v, noise) = noise_parameters(args, 1, "lambertian noise scale [seed]")?;
+            Rc::new(Lambertian::textured(Rc::new(NoiseTexture::new(noise, v[0]))))
+        },
+        ["lambertian", "turbulence", args @ ..] => {
+            let (v, noise) = noise_parameters(args, 2, "lambertian turbulence scale octaves [seed]")?;
+            Rc::new(Lambertian::textured(Rc::new(TurbulenceTexture::new(noise, v[0], v[1] as u32))))
+        },
+        ["lambertian", "marble", args @ ..] => {
+            let (v, noise) = noise_parameters(args, 2, "lambertian marble scale octaves [seed]")?;
+            Rc::new(Lambertian::textured(Rc::new(MarbleTexture::new(noise, v[0], v[1] as u32))))
+        },
+        ["lambertian", args @ ..] => {
+            let v = numbers(args, 3, "lambertian r g b")?;
+            Rc::new(Lambertian::new(Color::new(v[0], v[1], v[2])))
+        },
+        ["metal", args @ ..] => {
+            let v = numbers(args, 4, "metal r g b roughness")?;
+            Rc::new(Metal::new(Color::new(v[0], v[1], v[2]), v[3]))
+        },
+        ["plastic", args @ ..] => {
+            let v = numbers(args, 5, "plastic r g b ior roughness")?;
+            Rc::new(Plastic::new(Color::new(v[0], v[1], v[2]), v[3], v[4]))
+        },
+        ["dielectric", args @ ..] => {
+            let v = numbers(args, 1, "dielectric ior")?;
+            Rc::new(Dielectric::new(v[0]))
+        },
+        _ => return Err("expected a lambertian, metal, plastic or dielectric material".to_string()),
+    })
+}
+
+fn parse_object(kind: &str, args: &[&str], materials: &MaterialLibrary) -> Result<Rc<dyn Hit>, String> {
+    let usage = match kind {
+        "sphere" => "sphere cx cy cz radius material",
+        "plane" => "plane px py pz nx ny nz material",
+        "disk" => "disk cx cy cz nx ny nz radius material",
+        "cylinder" => "cylinder bx by bz radius height capped|open material",
+        "cone" => "cone bx by bz radius height capped|open material",
+        "torus" => "torus cx cy cz major_radius minor_radius material",
+        "cuboid" => "cuboid ax ay az bx by bz material",
+        _ => return Err(format!("unknown statement `{}`", kind)),
+    };
+    let (material, args) = args.split_last().ok_or_else(|| format!("expected `{}`", usage))?;
+    let material = materials.get(material).ok_or_else(|| format!("unknown material `{}`", material))?;
+    let capped = |flag: Option<&&str>| match flag {
+        Some(&"capped") => Ok(true),
+        Some(&"open") => Ok(false),
+        _ => Err(format!("expected `{}`", usage)),
+    };
+    let p = |v: &[Float]| Point3::new(v[0], v[1], v[2]);
+
+    Ok(match kind {
+        "sphere" => {
+            let v = numbers(args, 4, usage)?;
+            Rc::new(Sphere::new(p(&v), v[3], material))
+        },
+        "plane" => {
+            let v = numbers(args, 6, usage)?;
+            Rc::new(Plane::new(p(&v), Vec3::new(v[3], v[4], v[5]), material))
+        },
+        "disk" => {
+            let v = numbers(args, 7, usage)?;
+            Rc::new(Disk::new(p(&v), Vec3::new(v[3], v[4], v[5]), v[6], material))
+        },
+        "cylinder" | "cone" => {
+            let capped = capped(args.last())?;
+            let v = numbers(&args[..args.len() - 1], 5, usage)?;
+            if kind == "cylinder" {
+                Rc::new(Cylinder::new(p(&v), v[3], v[4], capped, material))
+            } else {
+                Rc::new(Cone::new(p(&v), v[3], v[4], capped, material))
+            }
+        },
+        "torus" => {
+            let v = numbers(args, 5, usage)?;
+            Rc::new(Torus::new(p(&v), v[3], v[4], material))
+        },
+        _ => {
+            let v = numbers(args, 6, usage)?;
+            Rc::new(Cuboid::new(p(&v), p(&v[3..]), material))
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::Ray;
+    use crate::mesh::{Mesh, MeshData};
+
+    fn view() -> Keyframe {
+        Keyframe {
+            time: 0.0,
+            origin: Point3::new(13.0, 2.0, 3.0),
+            destination: Point3::new(0.0, 0.0, 0.0),
+            fov: Degrees(20.0),
+            focus_dist: 10.0,
+        }
+    }
+
+    fn world() -> HittableList {
+        let shared: Rc<dyn Material> = Rc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.1));
+        let mut world = HittableList::default();
+        world.add(Rc::new(Plane::new(Point3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))));
+        world.add(Rc::new(Sphere::new(Point3::new(0.1, 1.0, -0.3), 1.0 / 3.0, Rc::new(Dielectric::new(1.5)))));
+        world.add(Rc::new(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, shared.clone())));
+        world.add(Rc::new(Disk::new(Point3::new(0.0, 3.0, 0.0), Vec3::new(0.0, 0.0, 1.0), 0.5, shared.clone())));
+        world.add(Rc::new(Cylinder::new(Point3::new(-4.0, 0.0, 0.0), 0.5, 2.0, true, shared.clone())));
+        world.add(Rc::new(Cone::new(Point3::new(-2.0, 0.0, 2.0), 0.5, 1.5, false, shared.clone())));
+        world.add(Rc::new(Torus::new(Point3::new(2.0, 0.5, 2.0), 0.8, 0.2, Rc::new(Lambertian::textured(Rc::new(MarbleTexture::new(Perlin::new(), 4.0, 7)))))));
+        world.add(Rc::new(Cuboid::new(Point3::new(-1.0, 0.0, -3.0), Point3::new(0.0, 1.0, -2.0), shared)));
+        world.add(Rc::new(Sphere::new(Point3::new(2.0, 0.3, -2.0), 0.3, Rc::new(Plastic::new(Color::new(0.1, 0.2, 0.7), 1.5, 0.1)))));
+        world
+    }
+
+    #[test]
+    fn round_trip_is_exact() {
+        let (text, skipped) = export(&world(), &MaterialLibrary::new(), &view(), &Settings::default());
+        assert_eq!(0, skipped);
+        assert_eq!(1, text.matches("metal").count());
+
+        let loaded = parse(&text).unwrap();
+        let view = loaded.view.unwrap();
+        assert_eq!(Point3::new(13.0, 2.0, 3.0), view.origin);
+        assert_eq!(text, export(&loaded.world, &loaded.materials, &view, &Settings::default()).0);
+
+        let original = world();
+        for k in 0..50 {
+            let a = k as Float * 0.41;
+            let ray = Ray::new(Point3::new(10.0 * a.cos(), 2.0, 10.0 * a.sin()), Vec3::new(-a.cos(), -0.15, -a.sin() + 0.1));
+            let expected = original.hit(&ray, 0.001, Float::INFINITY).map(|rec| (rec.t, rec.object_id));
+            assert_eq!(expected, loaded.world.hit(&ray, 0.001, Float::INFINITY).map(|rec| (rec.t, rec.object_id)));
+        }
+    }
+
+    #[test]
+    fn noise_textures_round_trip_exactly() {
+        let textured = |texture: Rc<dyn crate::texture::Texture>| -> Rc<dyn Material> { Rc::new(Lambertian::textured(texture)) };
+        let mut world = HittableList::default();
+        world.add(Rc::new(Sphere::new(Point3::new(-3.0, 0.0, 0.0), 1.0, textured(Rc::new(NoiseTexture::new(Perlin::new(), 8.0))))));
+        world.add(Rc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, textured(Rc::new(TurbulenceTexture::new(Perlin::new(), 4.0, 7))))));
+        world.add(Rc::new(Sphere::new(Point3::new(3.0, 0.0, 0.0), 1.0, textured(Rc::new(MarbleTexture::new(Perlin::new(), 4.0, 7))))));
+
+        let (text, _) = export(&world, &MaterialLibrary::new(), &view(), &Settings::default());
+        let loaded = parse(&text).unwrap();
+        assert_eq!(text, export(&loaded.world, &loaded.materials, &view(), &Settings::default()).0);
+
+        for k in 0..300 {
+            let a = k as Float * 0.173;
+            let ray = Ray::new(Point3::new(3.0 * (k % 3) as Float - 3.0 + 0.5 * a.sin(), 0.5 * a.cos(), 5.0), Vec3::new(0.0, 0.0, -1.0));
+            let albedo = |world: &HittableList| world.hit(&ray, 0.0, Float::INFINITY).map(|rec| Vec3::from(rec.material.albedo(&rec)));
+            assert_eq!(albedo(&world), albedo(&loaded.world));
+        }
+
+        // Hand-written files may leave the seed out and get new noise.
+        assert!(parse("material m lambertian marble 4 7\n").is_ok());
+        assert!(parse("material m lambertian marble 4 7 -1\n").is_err());
+    }
+
+    #[test]
+    fn camera_options_round_trip() {
+        let args = "--projection fisheye-equisolid --f-stop 4 --aperture-blades 6 --autofocus 10,20 --sampler halton --filter lanczos --filter-radius 2.5";
+        let settings = Settings::from_args(args.split(' ').map(str::to_string)).unwrap();
+        let world = world();
+        let (text, _) = export(&world, &MaterialLibrary::new(), &view(), &settings);
+        assert!(text.contains("projection fisheye-equisolid\nlens 50 4 36 24\naperture blades 6\nautofocus 10 20\nsampler halton\nfilter lanczos 2.5\n"));
+
+        let loaded = parse(&text).unwrap();
+        let reloaded = Settings::from_args(loaded.options.into_iter()).unwrap();
+        assert_eq!(settings.projection, reloaded.projection);
+        assert_eq!(Some(6), reloaded.aperture_blades);
+        assert_eq!(text, export(&world, &MaterialLibrary::new(), &view(), &reloaded).0);
+
+        assert!(parse("projection pinhole\n").is_err());
+        assert!(parse("aperture mask masks/hexagon.pgm\n").unwrap().options.contains(&"masks/hexagon.pgm".to_string()));
+    }
+
+    #[test]
+    fn unsupported_objects_are_skipped() {
+        let mut world = world();
+        let data = MeshData { positions: vec![Point3::new(0.0, 0.0, 0.0); 3], triangles: vec![[0, 1, 2]], ..MeshData::default() };
+        world.add(Rc::new(Mesh::new(data, Rc::new(Dielectric::new(1.5))).unwrap()));
+        assert_eq!(1, export(&world, &MaterialLibrary::new(), &view(), &Settings::default()).1);
+    }
+
+    #[test]
+    fn materials_are_referred_to_by_name() {
+        let library = MaterialLibrary::new();
+        let mut world = HittableList::default();
+        for x in 0..3 {
+            world.add(Rc::new(Sphere::new(Point3::new(x as Float, 0.0, 0.0), 0.4, library.preset("diamond"))));
+        }
+        world.add(Rc::new(Sphere::new(Point3::new(0.0, 2.0, 0.0), 0.4, Rc::new(Dielectric::new(1.5)))));
+        let (text, _) = export(&world, &library, &view(), &Settings::default());
+        assert!(text.contains("material diamond dielectric 2.418\nsphere 0 0 0 0.4 diamond\n"));
+        assert!(text.contains("material m0 dielectric 1.5\n"));
+        assert_eq!(1, text.matches("material diamond").count());
+
+        // Presets need no declaration, and every use shares one instance.
+        let loaded = parse("sphere 0 0 0 1 gold\nsphere 0 3 0 1 gold\n").unwrap();
+        let rays = [0.0, 3.0].map(|y| Ray::new(Point3::new(0.0, y, 5.0), Vec3::new(0.0, 0.0, -1.0)));
+        let [a, b] = rays.map(|ray| loaded.world.hit(&ray, 0.001, Float::INFINITY).unwrap().material);
+        assert!(Rc::ptr_eq(&a, &b));
+        assert!(Rc::ptr_eq(&a, &loaded.materials.preset("gold")));
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = |text: &str| parse(text).err().unwrap();
+        assert_eq!("2: unknown material `obsidian`", error("material m0 dielectric 1.5\nsphere 0 0 0 1 obsidian\n"));
+        assert_eq!("2: expected `sphere cx cy cz radius material`", error("material m0 dielectric 1.5\nsphere 0 0 1 m0\n"));
+        assert_eq!("3: unknown statement `teapot`", error("# comment\n\nteapot 0 0 0\n"));
+        assert_eq!("2: expected `cylinder bx by bz radius height capped|open material`", error("material m lambertian 1 1 1\ncylinder 0 0 0 1 2 closed m\n"));
+        assert_eq!("1: invalid number `x`", error("material m metal x 0 0 0\n"));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::hit::{Hit, HitRecord};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::sphere::Sphere;
+use std::rc::Rc;
+
+const MAX_STEPS: u32 = 512;
+const HIT_DISTANCE: Float = 1e-4;
+const NORMAL_EPSILON: Float = 1e-4;
+
+/// A composable signed distance function, negative inside the surface.
+pub(crate) enum Sdf {
+    Sphere { center: Point3, radius: Float },
+    Cuboid { center: Point3, half_extents: Vec3 },
+    Round { inner: Box<Sdf>, radius: Float },
+    SmoothUnion { a: Box<Sdf>, b: Box<Sdf>, blend: Float },
+    /// Tiles space with copies of `inner`; a zero period leaves that axis unrepeated.
+    Repeat { inner: Box<Sdf>, period: Vec3 },
+    /// Adds `amplitude * sin(fx) sin(fy) sin(fz)` ripples to the surface.
+    Displace { inner: Box<Sdf>, amplitude: Float, frequency: Float },
+}
+
+impl Sdf {
+    pub fn sphere(center: Point3, radius: Float) -> Self {
+        Sdf::Sphere { center, radius }
+    }
+
+    pub fn cuboid(center: Point3, half_extents: Vec3) -> Self {
+        Sdf::Cuboid { center, half_extents }
+    }
+
+    pub fn round(self, radius: Float) -> Self {
+        Sdf::Round { inner: Box::new(self), radius }
+    }
+
+    pub fn smooth_union(self, other: Sdf, blend: Float) -> Self {
+        Sdf::SmoothUnion { a: Box::new(self), b: Box::new(other), blend }
+    }
+
+    pub fn repeat(self, period: Vec3) -> Self {
+        Sdf::Repeat { inner: Box::new(self), period }
+    }
+
+    pub fn displace(self, amplitude: Float, frequency: Float) -> Self {
+        Sdf::Displace { inner: Box::new(self), amplitude, frequency }
+    }
+
+    pub fn distance(&self, p: Vec3) -> Float {
+        match self {
+            Sdf::Sphere { center, radius } => (p - Vec3::from(*center)).length() - radius,
+            Sdf::Cuboid { center, half_extents } => {
+                let d = p - Vec3::from(*center);
+                let q = Vec3::new(
+                    d.x().abs() - half_extents.x(),
+                    d.y().abs() - half_extents.y(),
+                    d.z().abs() - half_extents.z(),
+                );
+                let outside = Vec3::new(q.x().max(0.0), q.y().max(0.0), q.z().max(0.0)).length();
+                outside + q.x().max(q.y()).max(q.z()).min(0.0)
+            },
+            Sdf::Round { inner, radius } => inner.distance(p) - radius,
+            Sdf::SmoothUnion { a, b, blend } => {
+                let (da, db) = (a.distance(p), b.distance(p));
+                let h = (0.5 + 0.5 * (db - da) / blend).clamp(0.0, 1.0);
+                db + (da - db) * h - blend * h * (1.0 - h)
+            },
+            Sdf::Repeat { inner, period } => {
+                let mut q = p;
+                for axis in 0..3 {
+                    if period[axis] > 0.0 {
+                        q[axis] -= period[axis] * (p[axis] / period[axis]).round();
+                    }
+                }
+                inner.distance(q)
+            },
+            Sdf::Displace { inner, amplitude, frequency } => {
+                let ripple = (frequency * p.x()).sin() * (frequency * p.y()).sin() * (frequency * p.z()).sin();
+                inner.distance(p) + amplitude * ripple
+            },
+        }
+    }
+
+    /// Upper bound on how fast the distance can change, used to keep sphere tracing steps safe.
+    fn lipschitz(&self) -> Float {
+        match self {
+            Sdf::Sphere { .. } | Sdf::Cuboid { .. } => 1.0,
+            Sdf::Round { inner, .. } | Sdf::Repeat { inner, .. } => inner.lipschitz(),
+            Sdf::SmoothUnion { a, b, .. } => a.lipschitz().max(b.lipschitz()),
+            Sdf::Displace { inner, amplitude, frequency } => {
+                inner.lipschitz() + amplitude.abs() * frequency.abs() * Float::sqrt(3.0)
+            },
+        }
+    }
+
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        // Tetrahedral central differences need four evaluations instead of six.
+        let h = NORMAL_EPSILON;
+        let k = [Vec3::new(1.0, -1.0, -1.0), Vec3::new(-1.0, -1.0, 1.0), Vec3::new(-1.0, 1.0, -1.0), Vec3::new(1.0, 1.0, 1.0)];
+        k.iter().fold(Vec3::default(), |sum, k| sum + *k * self.distance(p + *k * h))
+    }
+}
+
+pub(crate) struct SdfObject {
+    sdf: Sdf,
+    bounds: Aabb,
+    material: Rc<dyn Material>,
+}
+
+impl SdfObject {
+    /// Rays are only marched inside `bounds`, which must enclose the whole surface.
+    pub fn new(sdf: Sdf, bounds: Aabb, material: Rc<dyn Material>) -> Self {
+        SdfObject { sdf, bounds, material }
+    }
+}
+
+impl Hit for SdfObject {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        let (mut t, t_end) = self.bounds.clip(ray, t_min, t_max)?;
+        let speed = ray.direction.length() * self.sdf.lipschitz();
+        let origin = Vec3::from(ray.origin);
+
+        // Rays starting inside the object march towards the exit instead.
+        let sign = self.sdf.distance(origin + t * ray.direction).signum();
+        for _ in 0..MAX_STEPS {
+            let distance = sign * self.sdf.distance(origin + t * ray.direction);
+            if distance < HIT_DISTANCE {
+                if t <= t_min {
+                    return None;
+                }
+                let p = ray.at(t);
+                let outward_normal = self.sdf.gradient(Vec3::from(p)).unit_vec();
+                let mut rec = HitRecord::new(p, outward_normal, t, Sphere::uv(&outward_normal), self.material.clone());
+                // Leaving rays start beyond the band that counts as a hit.
+                rec.error = rec.error.max(2.0 * HIT_DISTANCE);
+                rec.set_face_normal(ray, &outward_normal);
+                return Some(rec);
+            }
+
+            t += distance / speed;
+            if t > t_end {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+
+    fn object(sdf: Sdf) -> SdfObject {
+        let bounds = Aabb::new(Point3::new(-5.0, -5.0, -5.0), Point3::new(5.0, 5.0, 5.0));
+        SdfObject::new(sdf, bounds, Rc::new(Lambertian::new(Color::default())))
+    }
+
+    #[test]
+    fn primitive_distances() {
+        let sphere = Sdf::sphere(Point3::new(0.0, 0.0, 0.0), 1.0);
+        assert!((sphere.distance(Vec3::new(3.0, 0.0, 0.0)) - 2.0).abs() < 1e-6);
+
+        let cuboid = Sdf::cuboid(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        assert!((cuboid.distance(Vec3::new(0.0, 0.5, 0.0)) + 0.5).abs() < 1e-6);
+        assert!((cuboid.distance(Vec3::new(2.0, 2.0, 0.0)) - Float::sqrt(2.0)).abs() < 1e-6);
+        assert!((cuboid.round(0.25).distance(Vec3::new(2.0, 0.0, 0.0)) - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn smooth_union_blends_below_both() {
+        let a = Sdf::sphere(Point3::new(-1.0, 0.0, 0.0), 1.0);
+        let b = Sdf::sphere(Point3::new(1.0, 0.0, 0.0), 1.0);
+        let blob = a.smooth_union(b, 0.5);
+        assert!(blob.distance(Vec3::new(0.0, 0.0, 0.0)) < 0.0);
+        assert!((blob.distance(Vec3::new(-3.0, 0.0, 0.0)) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn repetition_tiles_space() {
+        let grid = Sdf::sphere(Point3::new(0.0, 0.0, 0.0), 0.5).repeat(Vec3::new(2.0, 0.0, 2.0));
+        assert!((grid.distance(Vec3::new(4.0, 0.0, -6.0)) + 0.5).abs() < 1e-6);
+        assert!((grid.distance(Vec3::new(4.0, 3.0, -6.0)) - 2.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn traced_sphere_matches_analytic_hit() {
+        let sphere = object(Sdf::sphere(Point3::new(0.0, 0.0, 0.0), 1.0));
+        let ray = Ray::new(Point3::new(0.3, 0.2, 4.0), Vec3::new(0.0, 0.0, -2.0));
+        let rec = sphere.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        let expected = (4.0 - (1.0 as Float - 0.13).sqrt()) / 2.0;
+        assert!((rec.t - expected).abs() < 1e-3);
+        assert!(rec.front_face);
+        assert!((rec.normal - Vec3::from(rec.p)).length() < 1e-2);
+    }
+
+    #[test]
+    fn ray_inside_finds_exit() {
+        let sphere = object(Sdf::sphere(Point3::new(0.0, 0.0, 0.0), 1.0));
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let rec = sphere.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((rec.t - 1.0).abs() < 1e-3);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn displaced_surface_is_not_overstepped() {
+        let bumpy = object(Sdf::sphere(Point3::new(0.0, 0.0, 0.0), 2.0).displace(0.2, 6.0));
+        for i in 0..20 {
+            let y = -1.5 + 0.15 * i as Float;
+            let ray = Ray::new(Point3::new(0.37, y, 4.5), Vec3::new(0.0, 0.0, -1.0));
+            let rec = bumpy.hit(&ray, 0.001, Float::INFINITY).unwrap();
+            assert!(bumpy.sdf.distance(Vec3::from(rec.p)).abs() < 1e-3);
+            for step in 1..100 {
+                let before = Vec3::from(ray.at(rec.t * step as Float / 100.0));
+                assert!(bumpy.sdf.distance(before) > 0.0);
+            }
+        }
+    }
+}
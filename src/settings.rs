@@ -0,0 +1,264 @@
+use crate::float::Float;
+use crate::scene::SceneKind;
+use crate::camera::{Lens, Projection};
+use crate::angle::Degrees;
+use crate::animation::Interpolation;
+use crate::sampler::SamplerKind;
+use crate::filter::FilterKind;
+use crate::tone_mapping::PostProcess;
+use crate::output::{ImageFormat, ImageSettings};
+use crate::exr::ExrPrecision;
+use crate::aov::Aov;
+use crate::progress_bar::ProgressMode;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Pixel {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl FromStr for Pixel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut coords = s.split(',').map(|c| c.trim().parse::<u32>());
+        match (coords.next(), coords.next(), coords.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => Ok(Pixel { x, y }),
+            _ => Err(format!("expected `<x>,<y>` pixel coordinates, got `{}`", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Settings {
+    pub scene: SceneKind,
+    pub heightmap: Option<String>,
+    pub input: Option<String>,
+    pub export: Option<String>,
+    pub projection: Projection,
+    pub fov: Option<Degrees>,
+    pub sampler: SamplerKind,
+    pub filter: FilterKind,
+    pub filter_radius: Option<Float>,
+    pub post_process: PostProcess,
+    pub output: Option<String>,
+    pub output_format: ImageFormat,
+    pub frame_format: ImageFormat,
+    pub exr_precision: ExrPrecision,
+    pub aovs: Vec<Aov>,
+    pub denoise: bool,
+    pub keep_noisy: bool,
+    pub preview: bool,
+    pub progress: ProgressMode,
+    pub stats: bool,
+    pub stats_json: Option<String>,
+    pub lens: Option<Lens>,
+    pub aperture_blades: Option<u32>,
+    pub aperture_mask: Option<String>,
+    pub autofocus: Option<Pixel>,
+    pub seed: Option<u64>,
+    pub animation: Option<String>,
+    pub interpolation: Interpolation,
+    pub frames: u32,
+    pub output_dir: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            scene: SceneKind::default(),
+            heightmap: None,
+            input: None,
+            export: None,
+            projection: Projection::default(),
+            fov: None,
+            sampler: SamplerKind::default(),
+            filter: FilterKind::default(),
+            filter_radius: None,
+            post_process: PostProcess::default(),
+            output: None,
+            output_format: ImageFormat::Ppm,
+            frame_format: ImageFormat::Png,
+            exr_precision: ExrPrecision::default(),
+            aovs: Vec::new(),
+            denoise: false,
+            keep_noisy: false,
+            preview: false,
+            progress: ProgressMode::default(),
+            stats: false,
+            stats_json: None,
+            lens: None,
+            aperture_blades: None,
+            aperture_mask: None,
+            autofocus: None,
+            seed: None,
+            animation: None,
+            interpolation: Interpolation::default(),
+            frames: 48,
+            output_dir: ".".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut settings = Settings::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--scene" => settings.scene = Settings::value(&arg, args.next())?,
+                "--heightmap" => settings.heightmap = Some(Settings::value(&arg, args.next())?),
+                "--input" => settings.input = Some(Settings::value(&arg, args.next())?),
+                "--export" => settings.export = Some(Settings::value(&arg, args.next())?),
+                "--projection" => settings.projection = Settings::value(&arg, args.next())?,
+                "--fov" => settings.fov = Some(Degrees(Settings::positive(&arg, args.next())?)),
+                "--sampler" => settings.sampler = Settings::value(&arg, args.next())?,
+                "--filter" => settings.filter = Settings::value(&arg, args.next())?,
+                "--filter-radius" => settings.filter_radius = Some(Settings::positive(&arg, args.next())?),
+                "--exposure" => settings.post_process.exposure = Settings::value(&arg, args.next())?,
+                "--tone-map" => settings.post_process.tone_mapping = Settings::value(&arg, args.next())?,
+                "--output" => {
+                    let path: String = Settings::value(&arg, args.next())?;
+                    settings.output_format = ImageFormat::from_path(&path)?;
+                    settings.output = Some(path);
+                }
+                "--frame-format" => settings.frame_format = Settings::value(&arg, args.next())?,
+                "--exr-precision" => settings.exr_precision = Settings::value(&arg, args.next())?,
+                "--aov" => {
+                    let list: String = Settings::value(&arg, args.next())?;
+                    settings.aovs = Aov::parse_list(&list)?;
+                }
+                "--denoise" => settings.denoise = true,
+                "--keep-noisy" => settings.keep_noisy = true,
+                "--preview" => settings.preview = true,
+                "--progress" => settings.progress = Settings::value(&arg, args.next())?,
+                "--stats" => settings.stats = true,
+                "--stats-json" => settings.stats_json = Some(Settings::value(&arg, args.next())?),
+                "--focal-length" => settings.lens_mut().focal_length = Settings::positive(&arg, args.next())?,
+                "--f-stop" => settings.lens_mut().f_stop = Settings::positive(&arg, args.next())?,
+                "--sensor" => {
+                    let size: String = Settings::value(&arg, args.next())?;
+                    let (width, height) = Settings::sensor_size(&size)?;
+                    let lens = settings.lens_mut();
+                    lens.sensor_width = width;
+                    lens.sensor_height = height;
+                }
+                "--aperture-blades" => {
+                    let blades = Settings::value(&arg, args.next())?;
+                    if blades < 3 {
+                        return Err(format!("`{}` needs at least 3 blades", arg));
+                    }
+                    settings.aperture_blades = Some(blades);
+                }
+                "--aperture-mask" => settings.aperture_mask = Some(Settings::value(&arg, args.next())?),
+                "--autofocus" => settings.autofocus = Some(Settings::value(&arg, args.next())?),
+                "--seed" => settings.seed = Some(Settings::value(&arg, args.next())?),
+                "--animation" => settings.animation = Some(Settings::value(&arg, args.next())?),
+                "--interpolation" => settings.interpolation = Settings::value(&arg, args.next())?,
+                "--frames" => {
+                    settings.frames = Settings::value(&arg, args.next())?;
+                    if settings.frames == 0 {
+                        return Err(format!("`{}` must be positive", arg));
+                    }
+                }
+                "--output-dir" => settings.output_dir = Settings::value(&arg, args.next())?,
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+
+        let to_stdout = settings.output.is_none() && settings.animation.is_none();
+        if !settings.aovs.is_empty() && to_stdout {
+            return Err("`--aov` needs `--output` to know where to write the extra images".to_string());
+        }
+        if settings.keep_noisy && !settings.denoise {
+            return Err("`--keep-noisy` only makes sense together with `--denoise`".to_string());
+        }
+        if settings.keep_noisy && to_stdout {
+            return Err("`--keep-noisy` needs `--output` to know where to write the extra image".to_string());
+        }
+        if settings.preview && settings.animation.is_some() {
+            return Err("`--preview` can't be combined with `--animation`".to_string());
+        }
+        if settings.heightmap.is_some() && settings.input.is_some() {
+            return Err("`--heightmap` can't be combined with `--input`".to_string());
+        }
+        if settings.heightmap.is_some() && settings.scene != SceneKind::Terrain {
+            return Err("`--heightmap` only applies to `--scene terrain`".to_string());
+        }
+        if let Some(pixel) = settings.autofocus {
+            let (width, height) = crate::image_size(settings.projection);
+            if pixel.x >= width || pixel.y >= height {
+                return Err(format!(
+                    "`--autofocus` pixel {},{} is outside the {}x{} image",
+                    pixel.x, pixel.y, width, height,
+                ));
+            }
+        }
+
+        Ok(settings)
+    }
+
+    pub fn image_settings(&self, format: ImageFormat) -> ImageSettings {
+        ImageSettings {
+            format,
+            post_process: self.post_process,
+            exr_precision: self.exr_precision,
+        }
+    }
+
+    fn lens_mut(&mut self) -> &mut Lens {
+        self.lens.get_or_insert_with(Lens::default)
+    }
+
+    fn sensor_size(size: &str) -> Result<(Float, Float), String> {
+        let mut dims = size.split('x').map(|d| d.trim().parse::<Float>());
+        match (dims.next(), dims.next(), dims.next()) {
+            (Some(Ok(w)), Some(Ok(h)), None) if w > 0.0 && h > 0.0 => Ok((w, h)),
+            _ => Err(format!("expected `<width>x<height>` sensor size in mm, got `{}`", size)),
+        }
+    }
+
+    fn positive(flag: &str, value: Option<String>) -> Result<Float, String> {
+        let value: Float = Settings::value(flag, value)?;
+        if value > 0.0 {
+            Ok(value)
+        } else {
+            Err(format!("`{}` must be positive", flag))
+        }
+    }
+
+    fn value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String>
+        where T::Err: ToString
+    {
+        value
+            .ok_or_else(|| format!("missing value for `{}`", flag))?
+            .parse()
+            .map_err(|e: T::Err| format!("invalid value for `{}`: {}", flag, e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Settings, String> {
+        Settings::from_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn autofocus_pixel_must_be_in_the_image() {
+        assert_eq!(Some(Pixel { x: 0, y: 0 }), parse(&["--autofocus", "0,0"]).unwrap().autofocus);
+        let (width, height) = crate::image_size(Projection::Perspective);
+        assert!(parse(&["--autofocus", &format!("{},{}", width - 1, height - 1)]).is_ok());
+        assert!(parse(&["--autofocus", &format!("{},0", width)]).is_err());
+        assert!(parse(&["--autofocus", &format!("0,{}", height)]).is_err());
+        assert!(parse(&["--projection", "equirectangular", "--autofocus", &format!("0,{}", width / 2)]).is_err());
+    }
+
+    #[test]
+    fn preview_conflicts_with_animation() {
+        assert!(parse(&["--preview"]).unwrap().preview);
+        assert!(parse(&["--preview", "--animation", "turntable"]).is_err());
+    }
+}
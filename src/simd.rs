@@ -0,0 +1,177 @@
+//! Four `Float` lanes, held in an SSE register on x86_64 with the `simd` feature and in a plain array
+//! everywhere else.
+
+#[cfg(all(feature = "simd", feature = "f64"))]
+compile_error!("the `simd` feature only covers `f32` math and can't be combined with `f64`");
+
+#[cfg(all(feature = "simd", not(feature = "f64"), target_arch = "x86_64"))]
+mod lanes {
+    use std::arch::x86_64::*;
+    use std::ops::{Add, Sub, Mul, Div};
+
+    #[derive(Copy, Clone, Debug)]
+    pub(crate) struct Float4(__m128);
+
+    impl Float4 {
+        pub const BACKEND: &'static str = "sse";
+
+        pub fn splat(value: f32) -> Self {
+            Float4(unsafe { _mm_set1_ps(value) })
+        }
+
+        pub fn from_array(values: [f32; 4]) -> Self {
+            Float4(unsafe { _mm_loadu_ps(values.as_ptr()) })
+        }
+
+        pub fn to_array(self) -> [f32; 4] {
+            let mut values = [0.0; 4];
+            unsafe { _mm_storeu_ps(values.as_mut_ptr(), self.0) };
+            values
+        }
+
+        pub fn min(self, rhs: Self) -> Self {
+            Float4(unsafe { _mm_min_ps(self.0, rhs.0) })
+        }
+
+        pub fn max(self, rhs: Self) -> Self {
+            Float4(unsafe { _mm_max_ps(self.0, rhs.0) })
+        }
+
+        /// Bit `i` is set where lane `i` of `self` is less than or equal to that of `rhs`.
+        pub fn le_mask(self, rhs: Self) -> u8 {
+            unsafe { _mm_movemask_ps(_mm_cmple_ps(self.0, rhs.0)) as u8 }
+        }
+    }
+
+    impl Add for Float4 {
+        type Output = Float4;
+
+        fn add(self, rhs: Float4) -> Self::Output {
+            Float4(unsafe { _mm_add_ps(self.0, rhs.0) })
+        }
+    }
+
+    impl Sub for Float4 {
+        type Output = Float4;
+
+        fn sub(self, rhs: Float4) -> Self::Output {
+            Float4(unsafe { _mm_sub_ps(self.0, rhs.0) })
+        }
+    }
+
+    impl Mul for Float4 {
+        type Output = Float4;
+
+        fn mul(self, rhs: Float4) -> Self::Output {
+            Float4(unsafe { _mm_mul_ps(self.0, rhs.0) })
+        }
+    }
+
+    impl Div for Float4 {
+        type Output = Float4;
+
+        fn div(self, rhs: Float4) -> Self::Output {
+            Float4(unsafe { _mm_div_ps(self.0, rhs.0) })
+        }
+    }
+}
+
+#[cfg(not(all(feature = "simd", not(feature = "f64"), target_arch = "x86_64")))]
+mod lanes {
+    use crate::float::Float;
+    use std::ops::{Add, Sub, Mul, Div};
+
+    #[derive(Copy, Clone, Debug)]
+    pub(crate) struct Float4([Float; 4]);
+
+    impl Float4 {
+        pub const BACKEND: &'static str = "scalar";
+
+        pub fn splat(value: Float) -> Self {
+            Float4([value; 4])
+        }
+
+        pub fn from_array(values: [Float; 4]) -> Self {
+            Float4(values)
+        }
+
+        pub fn to_array(self) -> [Float; 4] {
+            self.0
+        }
+
+        pub fn min(self, rhs: Self) -> Self {
+            self.zip(rhs, Float::min)
+        }
+
+        pub fn max(self, rhs: Self) -> Self {
+            self.zip(rhs, Float::max)
+        }
+
+        /// Bit `i` is set where lane `i` of `self` is less than or equal to that of `rhs`.
+        pub fn le_mask(self, rhs: Self) -> u8 {
+            (0..4).filter(|i| self.0[*i] <= rhs.0[*i]).fold(0, |mask, i| mask | 1 << i)
+        }
+
+        fn zip(self, rhs: Self, f: impl Fn(Float, Float) -> Float) -> Self {
+            Float4([f(self.0[0], rhs.0[0]), f(self.0[1], rhs.0[1]), f(self.0[2], rhs.0[2]), f(self.0[3], rhs.0[3])])
+        }
+    }
+
+    impl Add for Float4 {
+        type Output = Float4;
+
+        fn add(self, rhs: Float4) -> Self::Output {
+            self.zip(rhs, |a, b| a + b)
+        }
+    }
+
+    impl Sub for Float4 {
+        type Output = Float4;
+
+        fn sub(self, rhs: Float4) -> Self::Output {
+            self.zip(rhs, |a, b| a - b)
+        }
+    }
+
+    impl Mul for Float4 {
+        type Output = Float4;
+
+        fn mul(self, rhs: Float4) -> Self::Output {
+            self.zip(rhs, |a, b| a * b)
+        }
+    }
+
+    impl Div for Float4 {
+        type Output = Float4;
+
+        fn div(self, rhs: Float4) -> Self::Output {
+            self.zip(rhs, |a, b| a / b)
+        }
+    }
+}
+
+pub(crate) use lanes::Float4;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::float::Float;
+
+    #[test]
+    fn lanewise_arithmetic() {
+        let a = Float4::from_array([1.0, 2.0, 3.0, 4.0]);
+        let b = Float4::splat(2.0);
+        assert_eq!([3.0, 4.0, 5.0, 6.0], (a + b).to_array());
+        assert_eq!([-1.0, 0.0, 1.0, 2.0], (a - b).to_array());
+        assert_eq!([2.0, 4.0, 6.0, 8.0], (a * b).to_array());
+        assert_eq!([0.5, 1.0, 1.5, 2.0], (a / b).to_array());
+        assert_eq!([1.0, 2.0, 2.0, 2.0], a.min(b).to_array());
+        assert_eq!([2.0, 2.0, 3.0, 4.0], a.max(b).to_array());
+    }
+
+    #[test]
+    fn comparison_mask() {
+        let a = Float4::from_array([1.0, 5.0, Float::NAN, 2.0]);
+        assert_eq!(0b1001, a.le_mask(Float4::splat(2.0)));
+    }
+}
@@ -1,24 +1,35 @@
+use crate::float::Float;
 use crate::point3::Point3;
-use crate::hit::{Hit, HitRecord};
+use crate::hit::{self, Hit, HitRecord};
 use crate::ray::Ray;
 use crate::vec3::Vec3;
 use std::rc::Rc;
 use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::csg::{Crossing, Solid, Span};
+use crate::scene_file::{self, SceneWriter};
+use crate::float::consts::PI;
 
 pub(crate) struct Sphere {
     center: Point3,
-    radius: f32,
+    radius: Float,
     material: Rc<dyn Material>
 }
 
 impl Sphere {
-    pub fn new(center: Point3, radius: f32, material: Rc<dyn Material>) -> Self {
+    pub fn new(center: Point3, radius: Float, material: Rc<dyn Material>) -> Self {
         Sphere { center, radius, material }
     }
+
+    pub(crate) fn uv(outward_normal: &Vec3) -> (Float, Float) {
+        let theta = Float::acos(-outward_normal.y());
+        let phi = Float::atan2(-outward_normal.z(), outward_normal.x()) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
 }
 
 impl Hit for Sphere {
-    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
         let oc = ray.origin - self.center.into();
         let a = ray.direction.length_squared();
         let half_b = Vec3::dot(&oc.into(), &ray.direction);
@@ -31,7 +42,7 @@ impl Hit for Sphere {
 
         let root = discriminant.sqrt();
 
-        let temp: f32;
+        let temp: Float;
         if t_min < (-half_b - root) / a && (-half_b - root) / a < t_max {
             temp = (-half_b - root) / a;
         } else if t_min < (-half_b + root) / a && (-half_b + root) / a < t_max {
@@ -41,13 +52,78 @@ impl Hit for Sphere {
         }
 
         let t = temp;
-        let p = ray.at(t);
-        let normal = (Vec3::from(p) - Vec3::from(self.center)) / self.radius;
-        let outward_normal = (Vec3::from(p) - Vec3::from(self.center)) / self.radius;
+        // The quadratic cancels at the scale of the center, so project the point back onto the surface,
+        // leaving only the rounding of the projection. Dividing by the radius keeps its sign, so a negative radius
+        // still flips the normal inward.
+        let outward_normal = ((Vec3::from(ray.at(t)) - Vec3::from(self.center)) / self.radius).unit_vec();
+        let p = self.center + self.radius * outward_normal;
 
-        let mut rec = HitRecord::new(p, normal, t, self.material.clone());
+        let mut rec = HitRecord::new(p, outward_normal, t, Sphere::uv(&outward_normal), self.material.clone());
+        rec.error = hit::rounding_error(hit::magnitude(self.center) + self.radius.abs());
         rec.set_face_normal(ray, &outward_normal);
 
         Some(rec)
     }
-}
\ No newline at end of file
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let radius = Vec3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - radius, self.center + radius))
+    }
+
+    fn export(&self, scene: &mut SceneWriter) -> Option<()> {
+        let material = scene.material(&self.material)?;
+        scene.entry(format!("sphere {} {} {}", scene_file::point(self.center), self.radius, material));
+        Some(())
+    }
+}
+
+impl Solid for Sphere {
+    fn spans(&self, ray: &Ray) -> Vec<Span> {
+        let oc = Vec3::from(ray.origin) - Vec3::from(self.center);
+        let a = ray.direction.length_squared();
+        let half_b = Vec3::dot(&oc, &ray.direction);
+        let c = oc.length_squared() - self.radius * self.radius;
+        let discriminant = half_b * half_b - a * c;
+        if discriminant <= 0.0 {
+            return Vec::new();
+        }
+
+        let root = discriminant.sqrt();
+        let crossing = |t: Float| {
+            let outward_normal = (Vec3::from(ray.at(t)) - Vec3::from(self.center)) / self.radius;
+            Crossing::new(t, outward_normal, Sphere::uv(&outward_normal), self.material.clone())
+        };
+        vec![Span { enter: crossing((-half_b - root) / a), exit: crossing((-half_b + root) / a) }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+
+    fn sphere(radius: Float) -> Sphere {
+        Sphere::new(Point3::new(0.0, 0.0, 0.0), radius, Rc::new(Lambertian::new(Color::default())))
+    }
+
+    #[test]
+    fn outside_hit_is_front_face() {
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = sphere(1.0).hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 4.0).abs() < 1e-6);
+        assert!((rec.p.z() - 1.0).abs() < 1e-6);
+        assert!(rec.front_face);
+        assert!((rec.normal.z() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn negative_radius_flips_the_normal() {
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = sphere(-1.0).hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 4.0).abs() < 1e-6);
+        assert!((rec.p.z() - 1.0).abs() < 1e-6);
+        assert!(!rec.front_face);
+        assert!((rec.normal.z() - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+static RAYS: AtomicU64 = AtomicU64::new(0);
+static INTERSECTION_TESTS: AtomicU64 = AtomicU64::new(0);
+static BVH_NODE_VISITS: AtomicU64 = AtomicU64::new(0);
+static PATHS: AtomicU64 = AtomicU64::new(0);
+static PATH_SEGMENTS: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn count_ray() {
+    RAYS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn count_intersection_tests(count: u64) {
+    INTERSECTION_TESTS.fetch_add(count, Ordering::Relaxed);
+}
+
+pub(crate) fn count_bvh_node_visits(count: u64) {
+    BVH_NODE_VISITS.fetch_add(count, Ordering::Relaxed);
+}
+
+pub(crate) fn count_path(segments: u64) {
+    PATHS.fetch_add(1, Ordering::Relaxed);
+    PATH_SEGMENTS.fetch_add(segments, Ordering::Relaxed);
+}
+
+pub(crate) fn rays() -> u64 {
+    RAYS.load(Ordering::Relaxed)
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) struct Counters {
+    pub rays: u64,
+    pub intersection_tests: u64,
+    pub bvh_node_visits: u64,
+    pub paths: u64,
+    pub path_segments: u64,
+}
+
+impl Counters {
+    fn current() -> Self {
+        Counters {
+            rays: RAYS.load(Ordering::Relaxed),
+            intersection_tests: INTERSECTION_TESTS.load(Ordering::Relaxed),
+            bvh_node_visits: BVH_NODE_VISITS.load(Ordering::Relaxed),
+            paths: PATHS.load(Ordering::Relaxed),
+            path_segments: PATH_SEGMENTS.load(Ordering::Relaxed),
+        }
+    }
+
+    fn since(self, start: Counters) -> Self {
+        Counters {
+            rays: self.rays - start.rays,
+            intersection_tests: self.intersection_tests - start.intersection_tests,
+            bvh_node_visits: self.bvh_node_visits - start.bvh_node_visits,
+            paths: self.paths - start.paths,
+            path_segments: self.path_segments - start.path_segments,
+        }
+    }
+}
+
+pub(crate) struct Stats {
+    start: Counters,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Stats { start: Counters::current(), phases: Vec::new() }
+    }
+
+    pub fn time<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => self.phases.push((phase, elapsed)),
+        }
+        result
+    }
+
+    pub fn report(&self) -> Report {
+        Report { counters: Counters::current().since(self.start), phases: self.phases.clone() }
+    }
+}
+
+pub(crate) struct Report {
+    counters: Counters,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Report {
+    fn phase(&self, name: &str) -> Duration {
+        self.phases
+            .iter()
+            .find(|(phase, _)| *phase == name)
+            .map_or(Duration::default(), |(_, duration)| *duration)
+    }
+
+    fn average_path_length(&self) -> f64 {
+        self.counters.path_segments as f64 / self.counters.paths.max(1) as f64
+    }
+
+    fn per_second(&self, count: u64) -> f64 {
+        let seconds = self.phase("render").as_secs_f64();
+        if seconds > 0.0 { count as f64 / seconds } else { 0.0 }
+    }
+
+    pub fn summary(&self) -> String {
+        let c = &self.counters;
+        let mut out = String::new();
+        let _ = writeln!(out, "Rays traced:         {}", c.rays);
+        let _ = writeln!(out, "Intersection tests:  {}", c.intersection_tests);
+        let _ = writeln!(out, "BVH node visits:     {}", c.bvh_node_visits);
+        let _ = writeln!(out, "Samples:             {}", c.paths);
+        let _ = writeln!(out, "Average path length: {:.2}", self.average_path_length());
+        let _ = writeln!(out, "Samples per second:  {:.0}", self.per_second(c.paths));
+        let _ = writeln!(out, "Rays per second:     {:.0}", self.per_second(c.rays));
+        for (phase, duration) in &self.phases {
+            let _ = writeln!(out, "{:<21}{:.3}s", format!("Time {}:", phase), duration.as_secs_f64());
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let c = &self.counters;
+        let phases = self.phases
+            .iter()
+            .map(|(phase, duration)| format!("\"{}\": {:.6}", phase, duration.as_secs_f64()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            "{{\n  \"rays\": {},\n  \"intersection_tests\": {},\n  \"bvh_node_visits\": {},\n  \"samples\": {},\n  \
+             \"average_path_length\": {:.4},\n  \"samples_per_second\": {:.1},\n  \"rays_per_second\": {:.1},\n  \
+             \"phase_seconds\": {{{}}}\n}}\n",
+            c.rays,
+            c.intersection_tests,
+            c.bvh_node_visits,
+            c.paths,
+            self.average_path_length(),
+            self.per_second(c.paths),
+            self.per_second(c.rays),
+            phases,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> Report {
+        Report {
+            counters: Counters { rays: 30, intersection_tests: 300, bvh_node_visits: 0, paths: 10, path_segments: 25 },
+            phases: vec![("scene", Duration::from_millis(500)), ("render", Duration::from_secs(2))],
+        }
+    }
+
+    #[test]
+    fn derived_rates() {
+        let report = report();
+        assert_eq!(2.5, report.average_path_length());
+        assert_eq!(15.0, report.per_second(report.counters.rays));
+    }
+
+    #[test]
+    fn json_report() {
+        let json = report().to_json();
+        assert!(json.contains("\"rays\": 30,"));
+        assert!(json.contains("\"samples_per_second\": 5.0,"));
+        assert!(json.contains("\"phase_seconds\": {\"scene\": 0.500000, \"render\": 2.000000}"));
+    }
+
+    #[test]
+    fn phases_accumulate() {
+        let mut stats = Stats::new();
+        assert_eq!(4, stats.time("render", || 2 + 2));
+        stats.time("render", || ());
+        assert_eq!(1, stats.phases.len());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::mesh::MeshData;
+use std::fs;
+
+const HEADER_LEN: usize = 80;
+const TRIANGLE_LEN: usize = 50;
+
+pub(crate) fn read(path: &str) -> Result<MeshData, String> {
+    let bytes = fs::read(path).map_err(|e| format!("can't read `{}`: {}", path, e))?;
+    parse(&bytes).map_err(|e| format!("`{}`: {}", path, e))
+}
+
+fn parse(bytes: &[u8]) -> Result<MeshData, String> {
+    // Binary headers may also start with "solid", but their floats and padding are never plain text.
+    let text = bytes.iter().all(|b| b.is_ascii_graphic() || b.is_ascii_whitespace());
+    if text && bytes.trim_ascii_start().starts_with(b"solid") {
+        parse_ascii(bytes)
+    } else {
+        parse_binary(bytes)
+    }
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<MeshData, String> {
+    let count = bytes
+        .get(HEADER_LEN..HEADER_LEN + 4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as usize)
+        .ok_or_else(|| "binary STL is shorter than its header".to_string())?;
+    let body = &bytes[HEADER_LEN + 4..];
+    if body.len() < count * TRIANGLE_LEN {
+        return Err(format!("binary STL is truncated: expected {} triangles, found {}", count, body.len() / TRIANGLE_LEN));
+    }
+
+    let mut data = MeshData::default();
+    for triangle in body.chunks_exact(TRIANGLE_LEN).take(count) {
+        // Skip the facet normal; flat shading recomputes it from the winding.
+        let float = |k: usize| f32::from_le_bytes([triangle[4 * k], triangle[4 * k + 1], triangle[4 * k + 2], triangle[4 * k + 3]]) as Float;
+        let n = data.positions.len();
+        for vertex in 1..4 {
+            data.positions.push(Point3::new(float(3 * vertex), float(3 * vertex + 1), float(3 * vertex + 2)));
+        }
+        data.triangles.push([n, n + 1, n + 2]);
+    }
+    Ok(data)
+}
+
+fn parse_ascii(bytes: &[u8]) -> Result<MeshData, String> {
+    let text = std::str::from_utf8(bytes).map_err(|_| "ASCII STL is not valid text".to_string())?;
+    let mut data = MeshData::default();
+    let mut facet = Vec::with_capacity(3);
+    let mut finished = false;
+
+    for (number, line) in text.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["vertex", x, y, z] => {
+                let coordinate = |s: &str| s.parse::<Float>().map_err(|_| format!("invalid number `{}` on line {}", s, number));
+                facet.push(Point3::new(coordinate(x)?, coordinate(y)?, coordinate(z)?));
+            },
+            ["vertex", ..] => return Err(format!("malformed vertex on line {}", number)),
+            ["endloop"] => {
+                if facet.len() != 3 {
+                    return Err(format!("facet ending on line {} has {} vertices, expected 3", number, facet.len()));
+                }
+                let n = data.positions.len();
+                data.positions.append(&mut facet);
+                data.triangles.push([n, n + 1, n + 2]);
+            },
+            ["endsolid", ..] => {
+                finished = true;
+                break;
+            },
+            _ => {},
+        }
+    }
+
+    if !finished {
+        return Err("ASCII STL is truncated: missing `endsolid`".to_string());
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TETRAHEDRON: [[f32; 3]; 4] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    const FACES: [[usize; 3]; 4] = [[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+
+    fn ascii() -> String {
+        let mut text = String::from("solid tetrahedron\n");
+        for face in FACES {
+            text += "  facet normal 0 0 0\n    outer loop\n";
+            for v in face {
+                let [x, y, z] = TETRAHEDRON[v];
+                text += &format!("      vertex {} {} {}\n", x, y, z);
+            }
+            text += "    endloop\n  endfacet\n";
+        }
+        text + "endsolid tetrahedron\n"
+    }
+
+    fn binary() -> Vec<u8> {
+        // Headers starting with "solid" are common in binary files too.
+        let mut bytes = b"solid exported as binary".to_vec();
+        bytes.resize(HEADER_LEN, 0);
+        bytes.extend((FACES.len() as u32).to_le_bytes());
+        for face in FACES {
+            bytes.extend([0u8; 12]);
+            for v in face {
+                bytes.extend(TETRAHEDRON[v].iter().flat_map(|c| c.to_le_bytes()));
+            }
+            bytes.extend([0u8; 2]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn ascii_and_binary_agree() {
+        let text = parse(ascii().as_bytes()).unwrap();
+        let binary = parse(&binary()).unwrap();
+        assert_eq!(12, text.positions.len());
+        assert_eq!(text.positions, binary.positions);
+        assert_eq!(text.triangles, binary.triangles);
+        assert!(binary.normals.is_empty() && binary.colors.is_empty());
+    }
+
+    #[test]
+    fn truncated_files_are_rejected() {
+        let mut bytes = binary();
+        bytes.truncate(bytes.len() - 60);
+        assert_eq!("binary STL is truncated: expected 4 triangles, found 2", parse(&bytes).err().unwrap());
+        assert_eq!("binary STL is shorter than its header", parse(&[0u8; 40]).err().unwrap());
+
+        let text = ascii();
+        let cut = &text[..text.len() / 2];
+        assert_eq!("ASCII STL is truncated: missing `endsolid`", parse(cut.as_bytes()).err().unwrap());
+    }
+
+    #[test]
+    fn malformed_facets_are_rejected() {
+        let text = "solid broken\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nendloop\nendfacet\nendsolid\n";
+        assert_eq!("facet ending on line 6 has 2 vertices, expected 3", parse(text.as_bytes()).err().unwrap());
+        let text = "solid broken\nfacet normal 0 0 1\nouter loop\nvertex 0 zero 0\n";
+        assert_eq!("invalid number `zero` on line 4", parse(text.as_bytes()).err().unwrap());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::point3::Point3;
+use crate::perlin::Perlin;
+use crate::vec3::Vec3;
+use crate::scene_file;
+
+pub(crate) trait Texture {
+    fn value(&self, u: Float, v: Float, p: &Point3) -> Color;
+
+    /// Scene file description of a diffuse material with this albedo.
+    fn export(&self) -> Option<String> {
+        None
+    }
+}
+
+#[derive(Copy, Clone, Default, Debug)]
+pub(crate) struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> Self {
+        SolidColor { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: Float, _v: Float, _p: &Point3) -> Color {
+        self.color
+    }
+
+    fn export(&self) -> Option<String> {
+        Some(scene_file::color(self.color))
+    }
+}
+
+pub(crate) struct NoiseTexture {
+    noise: Perlin,
+    scale: Float,
+}
+
+impl NoiseTexture {
+    pub fn new(noise: Perlin, scale: Float) -> Self {
+        NoiseTexture { noise, scale }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: Float, _v: Float, p: &Point3) -> Color {
+        let p = (self.scale * Vec3::from(*p)).into();
+        (0.5 * (1.0 + self.noise.noise(&p)) * Vec3::new(1.0, 1.0, 1.0)).into()
+    }
+
+    fn export(&self) -> Option<String> {
+        Some(format!("noise {} {}", self.scale, self.noise.seed()))
+    }
+}
+
+pub(crate) struct TurbulenceTexture {
+    noise: Perlin,
+    scale: Float,
+    octaves: u32,
+}
+
+impl TurbulenceTexture {
+    pub fn new(noise: Perlin, scale: Float, octaves: u32) -> Self {
+        TurbulenceTexture { noise, scale, octaves }
+    }
+}
+
+impl Texture for TurbulenceTexture {
+    fn value(&self, _u: Float, _v: Float, p: &Point3) -> Color {
+        let p = (self.scale * Vec3::from(*p)).into();
+        (self.noise.turbulence(&p, self.octaves) * Vec3::new(1.0, 1.0, 1.0)).into()
+    }
+
+    fn export(&self) -> Option<String> {
+        Some(format!("turbulence {} {} {}", self.scale, self.octaves, self.noise.seed()))
+    }
+}
+
+pub(crate) struct MarbleTexture {
+    noise: Perlin,
+    scale: Float,
+    octaves: u32,
+}
+
+impl MarbleTexture {
+    pub fn new(noise: Perlin, scale: Float, octaves: u32) -> Self {
+        MarbleTexture { noise, scale, octaves }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: Float, _v: Float, p: &Point3) -> Color {
+        let phase = self.scale * p.z() + 10.0 * self.noise.turbulence(p, self.octaves);
+        (0.5 * (1.0 + phase.sin()) * Vec3::new(1.0, 1.0, 1.0)).into()
+    }
+
+    fn export(&self) -> Option<String> {
+        Some(format!("marble {} {} {}", self.scale, self.octaves, self.noise.seed()))
+    }
+}
+
+/// Bitmap texture with rows stored from the top and wrapping texture coordinates.
+pub(crate) struct ImageTexture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl ImageTexture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(width * height, pixels.len());
+        ImageTexture { width, height, pixels }
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: Float, v: Float, _p: &Point3) -> Color {
+        let i = ((u - u.floor()) * self.width as Float) as usize;
+        let j = ((1.0 - (v - v.floor())) * self.height as Float) as usize;
+        self.pixels[j.min(self.height - 1) * self.width + i.min(self.width - 1)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn textures(seed: u64) -> Vec<Box<dyn Texture>> {
+        vec![
+            Box::new(NoiseTexture::new(Perlin::with_seed(seed), 4.0)),
+            Box::new(TurbulenceTexture::new(Perlin::with_seed(seed), 4.0, 7)),
+            Box::new(MarbleTexture::new(Perlin::with_seed(seed), 4.0, 7)),
+        ]
+    }
+
+    fn points() -> impl Iterator<Item = Point3> {
+        (0..500).map(|i| {
+            let i = i as Float;
+            Point3::new(0.37 * i - 90.0, (0.71 * i).sin() * 5.0, 0.13 * i * i % 40.0 - 20.0)
+        })
+    }
+
+    #[test]
+    fn noise_textures_stay_in_range() {
+        for texture in textures(7) {
+            for p in points() {
+                let color = texture.value(0.0, 0.0, &p);
+                assert!((0.0..=1.0).contains(&color.r()), "{} at {:?}", color.r(), p);
+                assert_eq!(color.r(), color.g());
+                assert_eq!(color.r(), color.b());
+            }
+        }
+    }
+
+    #[test]
+    fn noise_textures_are_deterministic_for_a_seed() {
+        for (a, b) in textures(42).iter().zip(textures(42)) {
+            assert!(points().all(|p| a.value(0.0, 0.0, &p).r() == b.value(0.0, 0.0, &p).r()));
+        }
+        for (a, b) in textures(42).iter().zip(textures(43)) {
+            assert!(points().any(|p| a.value(0.0, 0.0, &p).r() != b.value(0.0, 0.0, &p).r()));
+        }
+    }
+}
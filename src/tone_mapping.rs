@@ -0,0 +1,157 @@
+use crate::float::Float;
+use crate::color::Color;
+use crate::vec3::Vec3;
+use std::str::FromStr;
+
+const ACES_INPUT: [[Float; 3]; 3] = [
+    [0.59719, 0.35458, 0.04823],
+    [0.07600, 0.90834, 0.01566],
+    [0.02840, 0.13383, 0.83777],
+];
+const ACES_OUTPUT: [[Float; 3]; 3] = [
+    [1.60475, -0.53108, -0.07367],
+    [-0.10208, 1.10813, -0.00605],
+    [-0.00327, -0.07276, 1.07602],
+];
+const AGX_INSET: [[Float; 3]; 3] = [
+    [0.842_479_06, 0.078_433_6, 0.079_223_745],
+    [0.042_328_242, 0.878_468_6, 0.079_166_13],
+    [0.042_375_654, 0.078_433_6, 0.879_143],
+];
+const AGX_OUTSET: [[Float; 3]; 3] = [
+    [1.196_879, -0.098_020_88, -0.099_029_74],
+    [-0.052_896_85, 1.151_903_1, -0.098_961_18],
+    [-0.052_971_635, -0.098_043_45, 1.151_073_7],
+];
+const AGX_MIN_EV: Float = -12.47393;
+const AGX_MAX_EV: Float = 4.026069;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) enum ToneMapping {
+    #[default]
+    None,
+    Reinhard,
+    Aces,
+    Agx,
+}
+
+impl FromStr for ToneMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(ToneMapping::None),
+            "reinhard" => Ok(ToneMapping::Reinhard),
+            "aces" => Ok(ToneMapping::Aces),
+            "agx" => Ok(ToneMapping::Agx),
+            _ => Err(format!("unknown tone mapping `{}`, expected one of: none, reinhard, aces, agx", s)),
+        }
+    }
+}
+
+impl ToneMapping {
+    pub fn apply(self, color: Vec3) -> Vec3 {
+        match self {
+            ToneMapping::None => color,
+            ToneMapping::Reinhard => color / (1.0 + luminance(color)),
+            ToneMapping::Aces => {
+                let mut v = transform(&ACES_INPUT, color);
+                v.iter_mut().for_each(|c| {
+                    *c = (*c * (*c + 0.024_578_6) - 0.000_090_537) / (*c * (0.983_729 * *c + 0.432_951) + 0.238_081)
+                });
+                transform(&ACES_OUTPUT, v)
+            }
+            ToneMapping::Agx => {
+                let mut v = transform(&AGX_INSET, color);
+                v.iter_mut().for_each(|c| {
+                    let x = (c.max(1e-10).log2().clamp(AGX_MIN_EV, AGX_MAX_EV) - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV);
+                    let x2 = x * x;
+                    let x4 = x2 * x2;
+                    *c = 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x - 0.00232;
+                });
+                let mut v = transform(&AGX_OUTSET, v);
+                v.iter_mut().for_each(|c| *c = c.max(0.0).powf(2.2));
+                v
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct PostProcess {
+    pub exposure: Float,
+    pub tone_mapping: ToneMapping,
+}
+
+impl PostProcess {
+    pub fn apply(&self, color: Color) -> Color {
+        let exposed = (2.0 as Float).powf(self.exposure) * Vec3::from(color);
+        let mut mapped = self.tone_mapping.apply(exposed);
+        mapped.iter_mut().for_each(|c| *c = srgb_encode(c.clamp(0.0, 1.0)));
+        mapped.into()
+    }
+}
+
+pub(crate) fn srgb_encode(linear: Float) -> Float {
+    if linear <= 0.003_130_8 {
+        12.92 * linear
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub(crate) fn srgb_decode(encoded: Float) -> Float {
+    if encoded <= 0.040_45 {
+        encoded / 12.92
+    } else {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn luminance(color: Vec3) -> Float {
+    Vec3::dot(&color, &Vec3::new(0.2126, 0.7152, 0.0722))
+}
+
+fn transform(matrix: &[[Float; 3]; 3], v: Vec3) -> Vec3 {
+    let mut result = Vec3::default();
+    for (r, row) in result.iter_mut().zip(matrix) {
+        *r = Vec3::dot(&Vec3::new(row[0], row[1], row[2]), &v);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_transfer() {
+        assert_eq!(0.0, srgb_encode(0.0));
+        assert!((srgb_encode(1.0) - 1.0).abs() < 1e-6);
+        assert!((srgb_encode(0.5) - 0.735_356_6).abs() < 1e-5);
+        for v in [0.001, 0.2, 0.5, 0.9] {
+            assert!((srgb_decode(srgb_encode(v)) - v).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn tone_mappers_compress_highlights() {
+        for tone_mapping in &[ToneMapping::Reinhard, ToneMapping::Aces, ToneMapping::Agx] {
+            let mut previous = 0.0;
+            for i in 1..100 {
+                let v = 0.1 * i as Float;
+                let mapped = tone_mapping.apply(Vec3::new(v, v, v)).y();
+                assert!(mapped >= previous && mapped <= 1.01, "{:?} at {}", tone_mapping, v);
+                previous = mapped;
+            }
+        }
+    }
+
+    #[test]
+    fn post_process_clamps() {
+        let post = PostProcess::default();
+        let color = post.apply(Color::new(4.0, -1.0, 0.5));
+        assert!((color.r() - 1.0).abs() < 1e-6);
+        assert_eq!(0.0, color.g());
+    }
+}
@@ -0,0 +1,141 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::hit::{Hit, HitRecord};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::roots::solve_quartic;
+use crate::scene_file::{self, SceneWriter};
+use crate::float::consts::PI;
+use std::rc::Rc;
+
+pub(crate) struct Torus {
+    center: Point3,
+    major_radius: Float,
+    minor_radius: Float,
+    material: Rc<dyn Material>,
+}
+
+impl Torus {
+    /// Torus lying in the horizontal plane through `center`.
+    pub fn new(center: Point3, major_radius: Float, minor_radius: Float, material: Rc<dyn Material>) -> Self {
+        Torus { center, major_radius, minor_radius, material }
+    }
+}
+
+impl Hit for Torus {
+    // The casts to f64 are no-ops in the `f64` build.
+    #[allow(clippy::unnecessary_cast)]
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitRecord> {
+        if !self.bounding_box()?.hit(ray, t_min, t_max) {
+            return None;
+        }
+
+        // Solve in f64 along a unit direction, starting from the ray point closest to the center
+        // so the quartic coefficients stay well conditioned for distant rays.
+        let length = ray.direction.length() as f64;
+        let d = [
+            ray.direction.x() as f64 / length,
+            ray.direction.y() as f64 / length,
+            ray.direction.z() as f64 / length,
+        ];
+        let oc = Vec3::from(ray.origin) - Vec3::from(self.center);
+        let mut o = [oc.x() as f64, oc.y() as f64, oc.z() as f64];
+        let shift = -(o[0] * d[0] + o[1] * d[1] + o[2] * d[2]);
+        for i in 0..3 {
+            o[i] += shift * d[i];
+        }
+
+        let (r2, a2) = ((self.major_radius as f64).powi(2), (self.minor_radius as f64).powi(2));
+        let od = o[0] * d[0] + o[1] * d[1] + o[2] * d[2];
+        let oo = o[0] * o[0] + o[1] * o[1] + o[2] * o[2];
+        let k = oo - r2 - a2;
+
+        let roots = solve_quartic(
+            4.0 * od,
+            2.0 * k + 4.0 * od * od + 4.0 * r2 * d[1] * d[1],
+            4.0 * k * od + 8.0 * r2 * o[1] * d[1],
+            k * k - 4.0 * r2 * (a2 - o[1] * o[1]),
+        );
+
+        let t = roots
+            .into_iter()
+            .map(|s| ((s + shift) / length) as Float)
+            .find(|t| *t > t_min && *t < t_max)?;
+
+        let p = ray.at(t);
+        let local = Vec3::from(p) - Vec3::from(self.center);
+        let ring_distance = (local.x() * local.x() + local.z() * local.z()).sqrt();
+        let ring = if ring_distance > 0.0 {
+            Vec3::new(local.x(), 0.0, local.z()) * (self.major_radius / ring_distance)
+        } else {
+            Vec3::default()
+        };
+        let outward_normal = (local - ring).unit_vec();
+
+        let u = (Float::atan2(-local.z(), local.x()) + PI) / (2.0 * PI);
+        let v = (Float::atan2(local.y(), ring_distance - self.major_radius) + PI) / (2.0 * PI);
+
+        let mut rec = HitRecord::new(p, outward_normal, t, (u, v), self.material.clone());
+        rec.set_face_normal(ray, &outward_normal);
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = self.major_radius + self.minor_radius;
+        let half = Vec3::new(extent, self.minor_radius, extent);
+        Some(Aabb::new(self.center - half, self.center + half))
+    }
+
+    fn export(&self, scene: &mut SceneWriter) -> Option<()> {
+        let material = scene.material(&self.material)?;
+        scene.entry(format!("torus {} {} {} {}", scene_file::point(self.center), self.major_radius, self.minor_radius, material));
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::material::Lambertian;
+
+    fn torus() -> Torus {
+        Torus::new(Point3::new(0.0, 1.0, 0.0), 2.0, 0.5, Rc::new(Lambertian::new(Color::default())))
+    }
+
+    #[test]
+    fn ray_along_axis_passes_through_hole() {
+        let ray = Ray::new(Point3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        assert!(torus().hit(&ray, 0.0, Float::INFINITY).is_none());
+    }
+
+    #[test]
+    fn ray_through_tube_hits_outer_wall_first() {
+        let ray = Ray::new(Point3::new(100.0, 1.0, 0.0), Vec3::new(-2.0, 0.0, 0.0));
+        let rec = torus().hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 48.75).abs() < 1e-3);
+        assert!((rec.normal.x() - 1.0).abs() < 1e-4);
+
+        let rec = torus().hit(&ray, 48.8, Float::INFINITY).unwrap();
+        assert!((rec.t - 49.25).abs() < 1e-3);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn top_of_tube() {
+        let ray = Ray::new(Point3::new(2.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let rec = torus().hit(&ray, 0.0, Float::INFINITY).unwrap();
+        assert!((rec.t - 3.5).abs() < 1e-4);
+        assert!((rec.normal.y() - 1.0).abs() < 1e-4);
+        assert!((rec.v - 0.75).abs() < 1e-4);
+    }
+
+    #[test]
+    fn torus_bounding_box() {
+        let bbox = torus().bounding_box().unwrap();
+        assert_eq!(Point3::new(-2.5, 0.5, -2.5), bbox.min);
+        assert_eq!(Point3::new(2.5, 1.5, 2.5), bbox.max);
+    }
+}
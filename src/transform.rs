@@ -0,0 +1,172 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::vec3::Vec3;
+use crate::angle::{Degrees, Radians};
+use crate::aabb::Aabb;
+
+/// Column-major affine matrix, the layout glTF uses.
+pub(crate) type Matrix = [[Float; 4]; 4];
+
+const IDENTITY: Matrix = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+
+/// Affine transform stored together with its inverse.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Transform {
+    matrix: Matrix,
+    inverse: Matrix,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform { matrix: IDENTITY, inverse: IDENTITY }
+    }
+
+    pub fn from_columns(matrix: Matrix) -> Self {
+        Transform { matrix, inverse: invert(&matrix) }
+    }
+
+    pub fn translate(offset: Vec3) -> Self {
+        let mut matrix = IDENTITY;
+        matrix[3] = [offset.x(), offset.y(), offset.z(), 1.0];
+        Transform::from_columns(matrix)
+    }
+
+    pub fn scale(factors: Vec3) -> Self {
+        let mut matrix = IDENTITY;
+        for axis in 0..3 {
+            matrix[axis][axis] = factors[axis as u8];
+        }
+        Transform::from_columns(matrix)
+    }
+
+    /// Counterclockwise rotation by `angle` when looking down `axis`.
+    pub fn rotate(axis: Vec3, angle: Degrees) -> Self {
+        let Radians(angle) = angle.into();
+        let (sin, cos) = angle.sin_cos();
+        let axis = axis.unit_vec();
+        let mut matrix = IDENTITY;
+        for (column, basis) in matrix.iter_mut().zip([Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)]) {
+            // Rodrigues' rotation of each basis vector.
+            let rotated = cos * basis + sin * axis.cross(&basis) + (1.0 - cos) * axis.dot(&basis) * axis;
+            *column = [rotated.x(), rotated.y(), rotated.z(), 0.0];
+        }
+        Transform::from_columns(matrix)
+    }
+
+    /// This transform followed by `next`.
+    pub fn then(&self, next: &Transform) -> Self {
+        Transform { matrix: multiply(&next.matrix, &self.matrix), inverse: multiply(&self.inverse, &next.inverse) }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Transform { matrix: self.inverse, inverse: self.matrix }
+    }
+
+    pub fn point(&self, p: Point3) -> Point3 {
+        let m = &self.matrix;
+        let row = |r: usize| m[0][r] * p.x() + m[1][r] * p.y() + m[2][r] * p.z() + m[3][r];
+        Point3::new(row(0), row(1), row(2))
+    }
+
+    pub fn vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.matrix;
+        let row = |r: usize| m[0][r] * v.x() + m[1][r] * v.y() + m[2][r] * v.z();
+        Vec3::new(row(0), row(1), row(2))
+    }
+
+    /// Normals transform with the inverse transpose to stay perpendicular to transformed surfaces.
+    pub fn normal(&self, n: Vec3) -> Vec3 {
+        let inverse = &self.inverse;
+        let component = |c: usize| column(inverse, c).dot(&n);
+        Vec3::new(component(0), component(1), component(2)).unit_vec()
+    }
+
+    /// Upper bound on how much the transform lengthens any vector.
+    pub fn max_stretch(&self) -> Float {
+        (0..3).map(|c| column(&self.matrix, c).length_squared()).sum::<Float>().sqrt()
+    }
+
+    /// Negative for transforms that mirror, which flips triangle winding.
+    pub fn determinant(&self) -> Float {
+        determinant(&self.matrix)
+    }
+
+    /// Box around the transformed corners of `bounds`.
+    pub fn bounding_box(&self, bounds: &Aabb) -> Aabb {
+        let corner = |i: usize| {
+            let pick = |axis: u8| if i & (1 << axis) == 0 { bounds.min[axis] } else { bounds.max[axis] };
+            self.point(Point3::new(pick(0), pick(1), pick(2)))
+        };
+        (1..8).fold(Aabb::new(corner(0), corner(0)), |acc, i| acc.surrounding(&Aabb::new(corner(i), corner(i))))
+    }
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut result = [[0.0; 4]; 4];
+    for (column, b_column) in result.iter_mut().zip(b) {
+        for (row, value) in column.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][row] * b_column[k]).sum();
+        }
+    }
+    result
+}
+
+fn column(m: &Matrix, c: usize) -> Vec3 {
+    Vec3::new(m[c][0], m[c][1], m[c][2])
+}
+
+fn determinant(m: &Matrix) -> Float {
+    column(m, 0).dot(&column(m, 1).cross(&column(m, 2)))
+}
+
+/// Inverse of an affine matrix. The rows of the inverse's linear part are the cross products of the columns.
+fn invert(m: &Matrix) -> Matrix {
+    let (a, b, c) = (column(m, 0), column(m, 1), column(m, 2));
+    let det = determinant(m);
+    let rows = [b.cross(&c) / det, c.cross(&a) / det, a.cross(&b) / det];
+    let translation = column(m, 3);
+
+    let mut inverse = IDENTITY;
+    for (j, inverse_column) in inverse.iter_mut().take(3).enumerate() {
+        *inverse_column = [rows[0][j as u8], rows[1][j as u8], rows[2][j as u8], 0.0];
+    }
+    inverse[3] = [-rows[0].dot(&translation), -rows[1].dot(&translation), -rows[2].dot(&translation), 1.0];
+    inverse
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Point3, b: Point3) -> bool {
+        (Vec3::from(a) - Vec3::from(b)).length() < 1e-5
+    }
+
+    #[test]
+    fn composition_and_inverse() {
+        let transform = Transform::scale(Vec3::new(2.0, 1.0, 0.5))
+            .then(&Transform::rotate(Vec3::new(0.0, 1.0, 0.0), Degrees(90.0)))
+            .then(&Transform::translate(Vec3::new(1.0, 2.0, 3.0)));
+        let p = transform.point(Point3::new(1.0, 1.0, 1.0));
+        assert!(close(Point3::new(1.5, 3.0, 1.0), p));
+        assert!(close(Point3::new(1.0, 1.0, 1.0), transform.inverse().point(p)));
+        assert!((transform.determinant() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn normals_stay_perpendicular() {
+        let transform = Transform::scale(Vec3::new(3.0, 1.0, 1.0)).then(&Transform::rotate(Vec3::new(1.0, 1.0, 0.0), Degrees(30.0)));
+        let (tangent, normal) = (Vec3::new(1.0, -1.0, 0.0), Vec3::new(1.0, 1.0, 0.0).unit_vec());
+        let n = transform.normal(normal);
+        assert!(transform.vector(tangent).dot(&n).abs() < 1e-5);
+        assert!((n.length() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn bounding_box_covers_rotated_corners() {
+        let unit = Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        let bounds = Transform::rotate(Vec3::new(0.0, 0.0, 1.0), Degrees(45.0)).bounding_box(&unit);
+        assert!((bounds.max.x() - Float::sqrt(2.0)).abs() < 1e-5);
+        assert!((bounds.max.z() - 1.0).abs() < 1e-5);
+    }
+}
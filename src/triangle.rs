@@ -0,0 +1,61 @@
+use crate::float::Float;
+use crate::point3::Point3;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// Möller–Trumbore ray/triangle test returning `t` and the barycentric weights of `v1` and `v2`.
+pub(crate) fn intersect_triangle(ray: &Ray, [v0, v1, v2]: [Point3; 3], t_min: Float, t_max: Float) -> Option<(Float, Float, Float)> {
+    let edge1 = Vec3::from(v1) - Vec3::from(v0);
+    let edge2 = Vec3::from(v2) - Vec3::from(v0);
+    let p = ray.direction.cross(&edge2);
+    let det = edge1.dot(&p);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let s = Vec3::from(ray.origin) - Vec3::from(v0);
+    let b1 = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let b2 = ray.direction.dot(&q) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(&q) * inv_det;
+    if t > t_min && t < t_max {
+        Some((t, b1, b2))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle() -> [Point3; 3] {
+        [Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0), Point3::new(0.0, 1.0, 0.0)]
+    }
+
+    #[test]
+    fn barycentrics_locate_hit_point() {
+        let ray = Ray::new(Point3::new(0.25, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        let (t, b1, b2) = intersect_triangle(&ray, triangle(), 0.0, Float::INFINITY).unwrap();
+        assert!((t - 2.0).abs() < 1e-6);
+        assert!((b1 - 0.25).abs() < 1e-6);
+        assert!((b2 - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn misses_outside_and_parallel() {
+        let outside = Ray::new(Point3::new(0.75, 0.75, 2.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(intersect_triangle(&outside, triangle(), 0.0, Float::INFINITY).is_none());
+        let parallel = Ray::new(Point3::new(0.2, 0.2, 1.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(intersect_triangle(&parallel, triangle(), 0.0, Float::INFINITY).is_none());
+    }
+}
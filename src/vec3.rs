@@ -1,49 +1,75 @@
+use crate::float::Float;
 use std::ops::{Neg, Index, IndexMut, AddAssign, MulAssign, DivAssign, Mul, Add, SubAssign, Sub, Div};
 use std::slice::{Iter, IterMut};
 use crate::point3::Point3;
 use crate::color::Color;
+use crate::random;
+#[cfg(feature = "simd")]
+use crate::simd::Float4;
 use rand::Rng;
-use std::f32::consts::PI;
+use crate::float::consts::PI;
 
-#[derive(Clone, Copy, Debug, Default)]
+/// Components, padded with a lane that stays zero to fill a SIMD register when the `simd` feature is on.
+#[cfg(not(feature = "simd"))]
+type Components = [Float; 3];
+#[cfg(feature = "simd")]
+type Components = [Float; 4];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "simd", repr(align(16)))]
 pub(crate) struct Vec3 {
-    pub(crate) vec: [f32; 3],
+    pub(crate) vec: Components,
 }
 
 impl Vec3 {
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
-        Vec3 { vec: [x, y, z] }
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
+        #[cfg(not(feature = "simd"))]
+        let vec = [x, y, z];
+        #[cfg(feature = "simd")]
+        let vec = [x, y, z, 0.0];
+        Vec3 { vec }
+    }
+
+    #[cfg(feature = "simd")]
+    fn lanes(self) -> Float4 {
+        Float4::from_array(self.vec)
     }
 
-    pub fn x(&self) -> f32 {
+    #[cfg(feature = "simd")]
+    fn from_lanes(lanes: Float4) -> Self {
+        Vec3 { vec: lanes.to_array() }
+    }
+
+    pub fn x(&self) -> Float {
         self[0]
     }
 
-    pub fn y(&self) -> f32 {
+    pub fn y(&self) -> Float {
         self[1]
     }
 
-    pub fn z(&self) -> f32 {
+    pub fn z(&self) -> Float {
         self[2]
     }
 
-    pub fn length(&self) -> f32 {
+    pub fn length(&self) -> Float {
         self.length_squared().sqrt()
     }
 
-    pub fn length_squared(&self) -> f32 {
-        self.iter().map(|a| a * a).sum()
+    pub fn length_squared(&self) -> Float {
+        self.dot(self)
     }
 
-    pub fn iter(&self) -> Iter<f32> {
-        self.vec.iter()
+    pub fn iter(&self) -> Iter<'_, Float> {
+        self.vec[..3].iter()
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<f32> {
-        self.vec.iter_mut()
+    pub fn iter_mut(&mut self) -> IterMut<'_, Float> {
+        self.vec[..3].iter_mut()
     }
 
-    pub fn dot(&self, rhs: &Vec3) -> f32 {
+    // Stays scalar with the `simd` feature: a horizontal SSE sum is slower than three multiply-adds.
+    pub fn dot(&self, rhs: &Vec3) -> Float {
         self.iter()
             .zip(rhs)
             .map(|(a, b)| *a * *b)
@@ -51,13 +77,11 @@ impl Vec3 {
     }
 
     pub fn cross(&self, rhs: &Vec3) -> Vec3 {
-        Vec3 {
-            vec: [
-                self[1] * rhs[2] - self[2] * rhs[1],
-                self[2] * rhs[0] - self[0] * rhs[2],
-                self[0] * rhs[1] - self[1] * rhs[0],
-            ]
-        }
+        Vec3::new(
+            self[1] * rhs[2] - self[2] * rhs[1],
+            self[2] * rhs[0] - self[0] * rhs[2],
+            self[0] * rhs[1] - self[1] * rhs[0],
+        )
     }
 
     pub fn unit_vec(self) -> Vec3 {
@@ -65,54 +89,47 @@ impl Vec3 {
     }
 
     pub fn random() -> Vec3 {
-        let mut rng = rand::thread_rng();
-        Vec3 {
-            vec: rng.gen(),
-        }
+        let mut rng = random::rng();
+        let [x, y, z]: [Float; 3] = rng.gen();
+        Vec3::new(x, y, z)
     }
 
-    pub fn random_range(min: f32, max: f32) -> Vec3 {
-        let mut rng = rand::thread_rng();
-        Vec3 {
-            vec: [rng.gen_range(min, max), rng.gen_range(min, max), rng.gen_range(min, max)],
-        }
+    pub fn random_range(min: Float, max: Float) -> Vec3 {
+        let mut rng = random::rng();
+        Vec3::new(rng.gen_range(min, max), rng.gen_range(min, max), rng.gen_range(min, max))
     }
 
-    pub fn random_in_unit_sphere() -> Vec3 {
-        loop {
-            let p = Vec3::random_range(-1.0, 1.0);
-            if p.length_squared() < 1.0 {
-                return p;
-            }
-        }
+    pub fn sample_in_unit_sphere((u1, u2): (Float, Float), u3: Float) -> Vec3 {
+        u3.cbrt() * Vec3::sample_unit_vector((u1, u2))
     }
 
-    pub fn random_unit_vector() -> Vec3 {
-        let mut rng = rand::thread_rng();
-        let a = rng.gen_range(0.0, 2.0 * PI);
-        let z = rng.gen_range(-1.0, 1.0);
-        let r = f32::sqrt(1.0 - z * z);
+    pub fn sample_unit_vector((u1, u2): (Float, Float)) -> Vec3 {
+        let a = 2.0 * PI * u1;
+        let z = 1.0 - 2.0 * u2;
+        let r = Float::sqrt(Float::max(0.0, 1.0 - z * z));
 
-        Vec3 {
-            vec: [r * f32::cos(a), r * f32::sin(a), z],
-        }
+        Vec3::new(r * Float::cos(a), r * Float::sin(a), z)
     }
 
-    pub fn random_in_unit_disk() -> Vec3 {
-        let mut rng = rand::thread_rng();
-        loop {
-            let p = Vec3::new(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0), 0.0);
-            if p.length_squared() < 1.0 {
-                return p;
-            }
+    pub fn sample_in_unit_disk((u1, u2): (Float, Float)) -> Vec3 {
+        let (x, y) = (2.0 * u1 - 1.0, 2.0 * u2 - 1.0);
+        if x == 0.0 && y == 0.0 {
+            return Vec3::default();
         }
+
+        let (r, theta) = if x.abs() > y.abs() {
+            (x, PI / 4.0 * (y / x))
+        } else {
+            (y, PI / 2.0 - PI / 4.0 * (x / y))
+        };
+        Vec3::new(r * theta.cos(), r * theta.sin(), 0.0)
     }
 
     pub fn reflect(&self, normal: &Vec3) -> Vec3 {
         *self - 2.0 * Vec3::dot(self, normal) * *normal
     }
 
-    pub fn refract(self, normal: Vec3, etai_over_etat: f32) -> Vec3 {
+    pub fn refract(self, normal: Vec3, etai_over_etat: Float) -> Vec3 {
         let cos_theta = Vec3::dot(&-self, &normal);
         let r_out_parallel: Vec3 = etai_over_etat * (self + cos_theta * normal);
         let r_out_perp = -(1.0 - r_out_parallel.length_squared()).sqrt() * normal;
@@ -124,12 +141,12 @@ impl Neg for Vec3 {
     type Output = Vec3;
 
     fn neg(self) -> Self::Output {
-        Vec3 { vec: [-self.x(), -self.y(), -self.z()] }
+        Vec3::new(-self.x(), -self.y(), -self.z())
     }
 }
 
 impl Index<u8> for Vec3 {
-    type Output = f32;
+    type Output = Float;
 
     fn index(&self, index: u8) -> &Self::Output {
         &self.vec[index as usize]
@@ -143,8 +160,8 @@ impl IndexMut<u8> for Vec3 {
 }
 
 impl<'a> IntoIterator for &'a Vec3 {
-    type Item = &'a f32;
-    type IntoIter = Iter<'a, f32>;
+    type Item = &'a Float;
+    type IntoIter = Iter<'a, Float>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
@@ -152,8 +169,8 @@ impl<'a> IntoIterator for &'a Vec3 {
 }
 
 impl<'a> IntoIterator for &'a mut Vec3 {
-    type Item = &'a mut f32;
-    type IntoIter = IterMut<'a, f32>;
+    type Item = &'a mut Float;
+    type IntoIter = IterMut<'a, Float>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
@@ -162,32 +179,29 @@ impl<'a> IntoIterator for &'a mut Vec3 {
 
 impl AddAssign<Vec3> for Vec3 {
     fn add_assign(&mut self, rhs: Vec3) {
-        for (a, b) in self.iter_mut().zip(&rhs) {
-            *a += *b;
-        }
+        *self = *self + rhs;
     }
 }
 
 impl SubAssign<Vec3> for Vec3 {
     fn sub_assign(&mut self, rhs: Vec3) {
-        for (a, b) in self.iter_mut().zip(&rhs) {
-            *a -= *b;
-        }
+        *self = *self - rhs;
     }
 }
 
-impl MulAssign<f32> for Vec3 {
-    fn mul_assign(&mut self, rhs: f32) {
-        self.iter_mut().for_each(|a| *a *= rhs);
+impl MulAssign<Float> for Vec3 {
+    fn mul_assign(&mut self, rhs: Float) {
+        *self = *self * rhs;
     }
 }
 
-impl DivAssign<f32> for Vec3 {
-    fn div_assign(&mut self, rhs: f32) {
-        self.iter_mut().for_each(|a| *a /= rhs);
+impl DivAssign<Float> for Vec3 {
+    fn div_assign(&mut self, rhs: Float) {
+        *self = *self / rhs;
     }
 }
 
+#[cfg(not(feature = "simd"))]
 impl Add<Vec3> for Vec3 {
     type Output = Vec3;
 
@@ -200,6 +214,15 @@ impl Add<Vec3> for Vec3 {
     }
 }
 
+#[cfg(feature = "simd")]
+impl Add<Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, rhs: Vec3) -> Self::Output {
+        Vec3::from_lanes(self.lanes() + rhs.lanes())
+    }
+}
+
 impl Add<Point3> for Vec3 {
     type Output = Point3;
 
@@ -208,6 +231,7 @@ impl Add<Point3> for Vec3 {
     }
 }
 
+#[cfg(not(feature = "simd"))]
 impl Sub<Vec3> for Vec3 {
     type Output = Vec3;
 
@@ -220,6 +244,15 @@ impl Sub<Vec3> for Vec3 {
     }
 }
 
+#[cfg(feature = "simd")]
+impl Sub<Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, rhs: Vec3) -> Self::Output {
+        Vec3::from_lanes(self.lanes() - rhs.lanes())
+    }
+}
+
 impl Sub<Point3> for Vec3 {
     type Output = Point3;
 
@@ -228,6 +261,7 @@ impl Sub<Point3> for Vec3 {
     }
 }
 
+#[cfg(not(feature = "simd"))]
 impl Mul<Vec3> for Vec3 {
     type Output = Vec3;
 
@@ -240,10 +274,20 @@ impl Mul<Vec3> for Vec3 {
     }
 }
 
-impl Mul<f32> for Vec3 {
+#[cfg(feature = "simd")]
+impl Mul<Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Vec3) -> Self::Output {
+        Vec3::from_lanes(self.lanes() * rhs.lanes())
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+impl Mul<Float> for Vec3 {
     type Output = Vec3;
 
-    fn mul(self, rhs: f32) -> Self::Output {
+    fn mul(self, rhs: Float) -> Self::Output {
         let mut result = Vec3::default();
         for (r, a) in result.iter_mut().zip(&self) {
             *r = *a * rhs;
@@ -252,7 +296,18 @@ impl Mul<f32> for Vec3 {
     }
 }
 
-impl Mul<Vec3> for f32 {
+#[cfg(feature = "simd")]
+impl Mul<Float> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Float) -> Self::Output {
+        // A zero factor in the padding lane keeps it zero even for infinite `rhs`.
+        Vec3::from_lanes(self.lanes() * Float4::from_array([rhs, rhs, rhs, 0.0]))
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+impl Mul<Vec3> for Float {
     type Output = Vec3;
 
     fn mul(self, rhs: Vec3) -> Self::Output {
@@ -264,10 +319,20 @@ impl Mul<Vec3> for f32 {
     }
 }
 
-impl Div<f32> for Vec3 {
+#[cfg(feature = "simd")]
+impl Mul<Vec3> for Float {
     type Output = Vec3;
 
-    fn div(self, rhs: f32) -> Self::Output {
+    fn mul(self, rhs: Vec3) -> Self::Output {
+        rhs * self
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+impl Div<Float> for Vec3 {
+    type Output = Vec3;
+
+    fn div(self, rhs: Float) -> Self::Output {
         let mut result = Vec3::default();
         for (r, a) in result.iter_mut().zip(&self) {
             *r = *a / rhs;
@@ -276,6 +341,16 @@ impl Div<f32> for Vec3 {
     }
 }
 
+#[cfg(feature = "simd")]
+impl Div<Float> for Vec3 {
+    type Output = Vec3;
+
+    fn div(self, rhs: Float) -> Self::Output {
+        // Dividing the padding lane by one keeps it zero even when `rhs` is.
+        Vec3::from_lanes(self.lanes() / Float4::from_array([rhs, rhs, rhs, 1.0]))
+    }
+}
+
 impl From<Point3> for Vec3 {
     fn from(p: Point3) -> Self {
         p.0
@@ -313,4 +388,13 @@ mod tests {
         assert_eq!(6.0, c.y());
         assert_eq!(-3.0, c.z());
     }
+
+    #[test]
+    fn sample_warps() {
+        for &sample in &[(0.0, 0.0), (0.25, 0.9), (0.5, 0.5), (0.999, 0.001)] {
+            assert!((Vec3::sample_unit_vector(sample).length() - 1.0).abs() < 1e-5);
+            assert!(Vec3::sample_in_unit_disk(sample).length() <= 1.0 + 1e-6);
+            assert!(Vec3::sample_in_unit_sphere(sample, 0.5).length() < 1.0);
+        }
+    }
 }
\ No newline at end of file